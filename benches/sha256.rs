@@ -0,0 +1,35 @@
+//! Witness calculation on the sha256 example, including setting the inputs and reading back the
+//! 29823 witness values. Run with `cargo bench --bench sha256`.
+
+use circom_scotia::{calculate_witness, r1cs::CircomConfig};
+use ff::Field;
+
+use pasta_curves::vesta::Base as Fr;
+use std::path::Path;
+use std::time::Instant;
+
+const ITERATIONS: u32 = 20;
+
+fn main() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/sha256");
+    let wtns = root.join("circom_sha256.wasm");
+    let r1cs = root.join("circom_sha256.r1cs");
+
+    let cfg = CircomConfig::<Fr>::new(wtns, r1cs).unwrap();
+    let input = || vec![("arg_in".into(), vec![Fr::ZERO, Fr::ZERO])];
+
+    // warm up
+    let witness = calculate_witness(&cfg, input(), true).unwrap();
+    assert_eq!(witness.len(), cfg.r1cs.num_variables);
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        calculate_witness(&cfg, input(), true).unwrap();
+    }
+    let elapsed = start.elapsed();
+
+    println!(
+        "sha256 witness calculation: {:?} per witness ({ITERATIONS} iterations)",
+        elapsed / ITERATIONS
+    );
+}
@@ -1,5 +1,5 @@
 use bellpepper_core::ConstraintSystem;
-use circom_scotia::{calculate_witness, r1cs::CircomConfig, synthesize};
+use circom_scotia::{calculate_witness, r1cs::CircomConfig, synthesize, synthesize_public};
 use ff::Field;
 
 use pasta_curves::vesta::Base as Fr;
@@ -23,7 +23,7 @@ fn main() {
     let output = synthesize(
         &mut cs.namespace(|| "sha256_circom"),
         cfg.r1cs.clone(),
-        Some(witness),
+        Some(witness.clone()),
     );
 
     let expected = "0x00000000008619b3767c057fdf8e6d99fde2680c5d8517eb06761c0878d40c40";
@@ -35,5 +35,20 @@ fn main() {
     assert_eq!(1, cs.num_inputs());
     assert_eq!(29822, cs.aux().len());
 
+    // the output and the public inputs can also be exposed as public inputs of the proof
+    let mut cs = TestConstraintSystem::<Fr>::new();
+    synthesize_public(
+        &mut cs.namespace(|| "sha256_circom"),
+        cfg.r1cs.clone(),
+        Some(witness),
+    )
+    .unwrap();
+
+    assert!(cs.is_satisfied());
+    assert_eq!(
+        1 + cfg.r1cs.num_pub_out + cfg.r1cs.num_pub_in,
+        cs.num_inputs()
+    );
+
     println!("Congrats! You synthesized and satisfied a circom sha256 circuit in bellpepper!");
 }
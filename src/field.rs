@@ -0,0 +1,68 @@
+// Copyright (c) Lurk Lab
+// SPDX-License-Identifier: MIT
+
+use ff::PrimeField;
+
+/// Conversions between a field element and its canonical integer value in little endian, which is
+/// how Circom's files and witness generators encode field elements.
+///
+/// The byte order of `PrimeField::Repr` is left to each implementation. The blanket implementation
+/// reads it from the encoding of one, so that fields with little and big endian reprs are both
+/// supported.
+pub trait CircomField: PrimeField {
+    /// Little endian bytes of the value, as many as in `Self::Repr`.
+    fn to_le_bytes(&self) -> Vec<u8>;
+
+    /// Element of value `bytes`, a little endian integer of any length. `None` if the value is not
+    /// smaller than the modulus.
+    fn from_le_bytes(bytes: &[u8]) -> Option<Self>;
+
+    /// Little endian `u32` limbs of the value.
+    fn to_le_limbs(&self) -> Vec<u32> {
+        self.to_le_bytes()
+            .chunks(4)
+            .map(|chunk| {
+                let mut bytes = [0u8; 4];
+                bytes[..chunk.len()].copy_from_slice(chunk);
+                u32::from_le_bytes(bytes)
+            })
+            .collect()
+    }
+
+    /// Element of value `limbs`, little endian `u32` limbs. `None` if the value is not smaller
+    /// than the modulus.
+    fn from_le_limbs(limbs: &[u32]) -> Option<Self> {
+        let bytes: Vec<u8> = limbs.iter().flat_map(|limb| limb.to_le_bytes()).collect();
+        Self::from_le_bytes(&bytes)
+    }
+}
+
+impl<F: PrimeField> CircomField for F {
+    fn to_le_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.to_repr().as_ref().to_vec();
+        if !repr_is_le::<F>() {
+            bytes.reverse();
+        }
+        bytes
+    }
+
+    fn from_le_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut repr = F::Repr::default();
+        let len = repr.as_ref().len().min(bytes.len());
+        let (low, high) = bytes.split_at(len);
+        if high.iter().any(|&b| b != 0) {
+            return None;
+        }
+        let buf = repr.as_mut();
+        buf[..len].copy_from_slice(low);
+        if !repr_is_le::<F>() {
+            buf.reverse();
+        }
+        F::from_repr(repr).into()
+    }
+}
+
+/// Whether `F::Repr` is little endian, i.e. whether one is encoded with a leading byte of 1.
+fn repr_is_le<F: PrimeField>() -> bool {
+    F::ONE.to_repr().as_ref().first() == Some(&1)
+}
@@ -0,0 +1,72 @@
+// Copyright (c) Lurk Lab
+// SPDX-License-Identifier: MIT
+
+use std::io;
+
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+use color_eyre::{eyre::eyre, Result};
+use ff::PrimeField;
+
+use crate::{calculate_witness, r1cs::CircomConfig, r1cs::PublicSignals, synthesize_with_inputs};
+
+/// A Circom circuit used as a bellpepper gadget: it takes allocated inputs, computes its own
+/// witness from their values and returns its public signals.
+///
+/// The circuit's symbol table must be attached to the configuration, see
+/// [`CircomConfig::with_sym`], to map input names to wires.
+#[derive(Debug)]
+pub struct CircomGadget<F: PrimeField> {
+    cfg: CircomConfig<F>,
+}
+
+impl<F: PrimeField> CircomGadget<F> {
+    pub fn new(cfg: CircomConfig<F>) -> Result<Self> {
+        if cfg.sym.is_none() {
+            return Err(eyre!(
+                "a symbol table is required to use a Circom circuit as a gadget"
+            ));
+        }
+        Ok(Self { cfg })
+    }
+
+    pub fn config(&self) -> &CircomConfig<F> {
+        &self.cfg
+    }
+
+    /// Synthesizes the circuit with its input signals bound to `inputs`.
+    ///
+    /// When every input has a value, the witness is calculated from them and assigned to all the
+    /// wires. Otherwise, e.g. during parameter generation, the circuit is synthesized without a
+    /// witness.
+    pub fn synthesize<CS: ConstraintSystem<F>, S: AsRef<str>>(
+        &self,
+        cs: &mut CS,
+        inputs: &[(S, Vec<AllocatedNum<F>>)],
+    ) -> Result<PublicSignals<F>, SynthesisError> {
+        let values = inputs
+            .iter()
+            .map(|(name, nums)| {
+                let values = nums
+                    .iter()
+                    .map(|n| n.get_value())
+                    .collect::<Option<Vec<_>>>()?;
+                Some((name.as_ref().to_string(), values))
+            })
+            .collect::<Option<Vec<_>>>();
+
+        let witness = match values {
+            Some(input) => Some(
+                calculate_witness(&self.cfg, input, self.cfg.sanity_check).map_err(|e| {
+                    SynthesisError::IoError(io::Error::new(
+                        io::ErrorKind::Other,
+                        format!("witness calculation failed: {e}"),
+                    ))
+                })?,
+            ),
+            None => None,
+        };
+
+        let sym = self.cfg.sym.as_ref().expect("checked in CircomGadget::new");
+        synthesize_with_inputs(cs, &self.cfg.r1cs, sym, inputs, witness)
+    }
+}
@@ -9,22 +9,29 @@
 //   - Retrofitted to support `wasmer` witness generation.
 
 use std::{
+    collections::HashMap,
     env::current_dir,
     fs,
+    io::Read,
     path::{Path, PathBuf},
     process::Command,
 };
 
 use bellpepper_core::{num::AllocatedNum, ConstraintSystem, LinearCombination, SynthesisError};
-use color_eyre::Result;
 use ff::PrimeField;
-use r1cs::{CircomConfig, R1CS};
+use r1cs::{CircomConfig, CircomInput, JsonWitnessError, PublicSignals, R1CS};
+use sym::SymbolTable;
 
 use crate::reader::load_witness_from_file;
+use crate::witness::WitnessError;
 
+pub mod field;
+pub mod gadget;
 pub mod r1cs;
 pub mod reader;
+pub mod sym;
 pub mod witness;
+pub mod writer;
 
 pub fn generate_witness_from_wasm<F: PrimeField>(
     witness_dir: PathBuf,
@@ -60,41 +67,154 @@ pub fn calculate_witness<F: PrimeField>(
     cfg: &CircomConfig<F>,
     input: Vec<(String, Vec<F>)>,
     sanity_check: bool,
-) -> Result<Vec<F>> {
-    let mut lock = cfg.wtns.lock().unwrap();
+) -> Result<Vec<F>, WitnessError> {
+    let mut lock = cfg.wtns.lock().map_err(|_| WitnessError::LockPoisoned)?;
     let witness_calculator = &mut *lock;
     witness_calculator.calculate_witness(input, sanity_check)
 }
 
+/// Calculates the witness of an input in Circom's `input.json` format, read from `reader`, see
+/// [`CircomInput`]. Sanity checks are enabled by `cfg.sanity_check`.
+pub fn calculate_witness_from_json<F: PrimeField>(
+    cfg: &CircomConfig<F>,
+    reader: impl Read,
+) -> Result<Vec<F>, JsonWitnessError> {
+    let input = CircomInput::from_reader(reader)?;
+    Ok(calculate_witness(cfg, input.signals, cfg.sanity_check)?)
+}
+
+/// Calculates the witnesses of a batch of inputs, in parallel on the instances of `cfg.pool` if
+/// set (see [`CircomConfig::with_pool`]), one after the other otherwise. Results are returned in
+/// the order of `inputs`.
+pub fn calculate_witnesses<F, I>(
+    cfg: &CircomConfig<F>,
+    inputs: I,
+    sanity_check: bool,
+) -> Vec<Result<Vec<F>, WitnessError>>
+where
+    F: PrimeField,
+    I: IntoIterator<Item = Vec<(String, Vec<F>)>>,
+    I::IntoIter: Send,
+{
+    match &cfg.pool {
+        Some(pool) => pool.calculate_witnesses(inputs, sanity_check),
+        None => inputs
+            .into_iter()
+            .map(|input| calculate_witness(cfg, input, sanity_check))
+            .collect(),
+    }
+}
+
 /// Reference work is Nota-Scotia: https://github.com/nalinbhardwaj/Nova-Scotia
 pub fn synthesize<F: PrimeField, CS: ConstraintSystem<F>>(
     cs: &mut CS,
     r1cs: R1CS<F>,
     witness: Option<Vec<F>>,
 ) -> Result<AllocatedNum<F>, SynthesisError> {
+    let vars = synthesize_inner(cs, &r1cs, witness.as_deref(), false, &HashMap::new())?;
+    Ok(vars[0].clone())
+}
+
+/// Same as [`synthesize`], but the public outputs and public inputs of the Circom circuit are
+/// allocated as public inputs of the constraint system, in Circom's order: outputs first, then
+/// public inputs.
+pub fn synthesize_public<F: PrimeField, CS: ConstraintSystem<F>>(
+    cs: &mut CS,
+    r1cs: R1CS<F>,
+    witness: Option<Vec<F>>,
+) -> Result<AllocatedNum<F>, SynthesisError> {
+    let vars = synthesize_inner(cs, &r1cs, witness.as_deref(), true, &HashMap::new())?;
+    Ok(vars[0].clone())
+}
+
+/// Same as [`synthesize`], but returns every public output and public input of the Circom
+/// circuit. When a symbol table is given, they are also grouped by signal name and shape.
+pub fn synthesize_with_signals<F: PrimeField, CS: ConstraintSystem<F>>(
+    cs: &mut CS,
+    r1cs: &R1CS<F>,
+    sym: Option<&SymbolTable>,
+    witness: Option<Vec<F>>,
+) -> Result<PublicSignals<F>, SynthesisError> {
+    let vars = synthesize_inner(cs, r1cs, witness.as_deref(), false, &HashMap::new())?;
+    Ok(PublicSignals::new(r1cs, sym, &vars))
+}
+
+/// Same as [`synthesize_with_signals`], but the input signals listed in `inputs` are bound to
+/// already allocated variables instead of fresh ones, so that a Circom circuit can be embedded in
+/// a larger bellpepper circuit. Input names are the ones of the main component, as given to
+/// [`calculate_witness`], and each input must be given all of its elements in row-major order.
+pub fn synthesize_with_inputs<F: PrimeField, CS: ConstraintSystem<F>, S: AsRef<str>>(
+    cs: &mut CS,
+    r1cs: &R1CS<F>,
+    sym: &SymbolTable,
+    inputs: &[(S, Vec<AllocatedNum<F>>)],
+    witness: Option<Vec<F>>,
+) -> Result<PublicSignals<F>, SynthesisError> {
+    let mut bound = HashMap::new();
+    for (name, nums) in inputs {
+        let name = name.as_ref();
+        let wires = sym.signal_wires(&format!("main.{name}")).ok_or_else(|| {
+            SynthesisError::IncompatibleLengthVector(format!(
+                "input signal {name} not found in the symbol table"
+            ))
+        })?;
+        if wires.len() != nums.len() {
+            return Err(SynthesisError::IncompatibleLengthVector(format!(
+                "input signal {name} has {} elements, {} given",
+                wires.len(),
+                nums.len()
+            )));
+        }
+        bound.extend(wires.into_iter().zip(nums.iter().cloned()));
+    }
+
+    let vars = synthesize_inner(cs, r1cs, witness.as_deref(), false, &bound)?;
+    Ok(PublicSignals::new(r1cs, Some(sym), &vars))
+}
+
+/// Allocates every wire but the constant one, enforces the constraints and returns the allocated
+/// wires, starting from wire 1. Wires found in `bound` reuse the given variable instead.
+pub(crate) fn synthesize_inner<F: PrimeField, CS: ConstraintSystem<F>>(
+    cs: &mut CS,
+    r1cs: &R1CS<F>,
+    witness: Option<&[F]>,
+    public: bool,
+    bound: &HashMap<usize, AllocatedNum<F>>,
+) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
     //println!("witness: {:?}", witness);
     //println!("num_inputs: {:?}", r1cs.num_inputs);
     //println!("num_aux: {:?}", r1cs.num_aux);
     //println!("num_variables: {:?}", r1cs.num_variables);
     //println!("num constraints: {:?}", r1cs.constraints.len());
 
-    let witness = &witness;
-
     let mut vars: Vec<AllocatedNum<F>> = vec![];
 
     for i in 1..r1cs.num_inputs {
+        if let Some(v) = bound.get(&i) {
+            vars.push(v.clone());
+            continue;
+        }
         let f: F = {
             match witness {
                 None => F::ONE,
                 Some(w) => w[i],
             }
         };
-        let v = AllocatedNum::alloc(cs.namespace(|| format!("public_{}", i)), || Ok(f))?;
+        let cs = cs.namespace(|| format!("public_{}", i));
+        let v = if public {
+            AllocatedNum::alloc_input(cs, || Ok(f))?
+        } else {
+            AllocatedNum::alloc(cs, || Ok(f))?
+        };
 
         vars.push(v);
     }
 
     for i in 0..r1cs.num_aux {
+        if let Some(v) = bound.get(&(i + r1cs.num_inputs)) {
+            vars.push(v.clone());
+            continue;
+        }
         // Private witness trace
         let f: F = {
             match witness {
@@ -107,9 +227,7 @@ pub fn synthesize<F: PrimeField, CS: ConstraintSystem<F>>(
         vars.push(v);
     }
 
-    let output = vars[0].clone();
-
-    let make_lc = |lc_data: Vec<(usize, F)>| {
+    let make_lc = |lc_data: &[(usize, F)]| {
         let res = lc_data.iter().fold(
             LinearCombination::<F>::zero(),
             |lc: LinearCombination<F>, (index, coeff)| {
@@ -123,14 +241,14 @@ pub fn synthesize<F: PrimeField, CS: ConstraintSystem<F>>(
         res
     };
 
-    for (i, constraint) in r1cs.constraints.into_iter().enumerate() {
+    for (i, constraint) in r1cs.constraints.iter().enumerate() {
         cs.enforce(
             || format!("constraint {}", i),
-            |_| make_lc(constraint.0),
-            |_| make_lc(constraint.1),
-            |_| make_lc(constraint.2),
+            |_| make_lc(&constraint.0),
+            |_| make_lc(&constraint.1),
+            |_| make_lc(&constraint.2),
         );
     }
 
-    Ok(output)
+    Ok(vars)
 }
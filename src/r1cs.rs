@@ -8,32 +8,499 @@
 //   - Adapted the original work here: https://github.com/nalinbhardwaj/Nova-Scotia/blob/main/src/circom
 //   - Retrofitted to support `wasmer` witness generation.
 
-use std::{io, path::Path, sync::Mutex};
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{self, Read, Seek, Write},
+    path::Path,
+    sync::Mutex,
+};
 
+use bellpepper_core::{num::AllocatedNum, Circuit, ConstraintSystem, SynthesisError};
+use color_eyre::Result;
 use ff::PrimeField;
-use serde::{Deserialize, Serialize};
+use itertools::Itertools;
 
-use crate::{reader::load_r1cs, witness::WitnessCalculator};
+use crate::{
+    calculate_witness,
+    reader::{load_r1cs_from_bin, try_load_r1cs, ReaderError},
+    sym::SymbolTable,
+    synthesize_inner,
+    witness::{WitnessCalculator, WitnessCalculatorPool, WitnessError},
+    writer::{to_decimal_string, write_r1cs},
+};
 
-#[allow(dead_code)]
+/// A Circom circuit, ready to be handed to a bellpepper prover. The public outputs and public
+/// inputs of the Circom circuit are allocated as public inputs, see [`crate::synthesize_public`].
+///
+/// Without a witness the circuit only describes its constraints, which is what parameter
+/// generation needs. With a witness it can be used to create a proof. The r1cs is borrowed, so
+/// that large circuits are not copied for every proof.
 #[derive(Clone)]
-pub(crate) struct CircomCircuit<F: PrimeField> {
-    r1cs: R1CS<F>,
-    witness: Option<Vec<F>>,
+pub struct CircomCircuit<'a, F: PrimeField> {
+    pub r1cs: &'a R1CS<F>,
+    pub witness: Option<Vec<F>>,
+}
+
+impl<'a, F: PrimeField> CircomCircuit<'a, F> {
+    pub fn new(r1cs: &'a R1CS<F>, witness: Option<Vec<F>>) -> Self {
+        Self { r1cs, witness }
+    }
+
+    /// Creates a witness-less circuit, for parameter generation.
+    pub fn without_witness(cfg: &'a CircomConfig<F>) -> Self {
+        Self::new(&cfg.r1cs, None)
+    }
+
+    /// Computes the witness for `input` and creates a circuit ready for proving.
+    pub fn with_input(cfg: &'a CircomConfig<F>, input: Vec<(String, Vec<F>)>) -> Result<Self> {
+        let witness = calculate_witness(cfg, input, cfg.sanity_check)?;
+        Ok(Self::new(&cfg.r1cs, Some(witness)))
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for CircomCircuit<'_, F> {
+    fn synthesize<CS: ConstraintSystem<F>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        synthesize_inner(
+            cs,
+            self.r1cs,
+            self.witness.as_deref(),
+            true,
+            &HashMap::new(),
+        )
+        .map(|_| ())
+    }
 }
 
-#[allow(dead_code)]
 #[derive(Clone, Debug)]
 pub struct R1CS<F: PrimeField> {
+    /// Byte size of a field element in the r1cs file.
+    pub field_size: usize,
+    /// Little endian encoding of the prime of the field.
+    pub prime: Vec<u8>,
     pub num_inputs: usize,
     pub num_aux: usize,
     pub num_variables: usize,
+    pub num_pub_out: usize,
+    pub num_pub_in: usize,
+    pub num_prv_in: usize,
+    pub num_labels: usize,
     pub constraints: Vec<Constraint<F>>,
+    /// Label id of each wire, as found in the wire2label section.
+    pub wire_mapping: Vec<usize>,
+}
+
+impl<F: PrimeField> R1CS<F> {
+    /// Reads an R1CS from its binary encoding.
+    pub fn from_reader<R: Read + Seek>(reader: R) -> Result<Self, ReaderError> {
+        load_r1cs_from_bin(reader)
+    }
+
+    /// Writes the R1CS in the iden3 binary encoding.
+    pub fn to_writer<W: Write>(&self, writer: W) -> io::Result<()> {
+        write_r1cs(self, writer)
+    }
+
+    /// Converts a label-indexed witness, such as the one of an unoptimized circuit, into the
+    /// wire-indexed witness expected by this r1cs. Returns `None` if a wire's label is out of
+    /// the bounds of `witness`.
+    pub fn wires_from_labels(&self, witness: &[F]) -> Option<Vec<F>> {
+        self.wire_mapping
+            .iter()
+            .map(|&label| witness.get(label).copied())
+            .collect()
+    }
+
+    /// Checks that a wire-indexed witness satisfies every constraint, by evaluating A·w, B·w and
+    /// C·w directly. Returns the unsatisfied constraints, an empty vector meaning the witness is
+    /// valid, or an error if the witness is too short for the r1cs.
+    pub fn check(&self, witness: &[F]) -> Result<Vec<UnsatisfiedConstraint<F>>, CheckError> {
+        self.check_constraints(witness, None)
+    }
+
+    /// Same as [`R1CS::check`], additionally naming the signals involved in each unsatisfied
+    /// constraint.
+    pub fn check_with_sym(
+        &self,
+        witness: &[F],
+        sym: &SymbolTable,
+    ) -> Result<Vec<UnsatisfiedConstraint<F>>, CheckError> {
+        self.check_constraints(witness, Some(sym))
+    }
+
+    fn check_constraints(
+        &self,
+        witness: &[F],
+        sym: Option<&SymbolTable>,
+    ) -> Result<Vec<UnsatisfiedConstraint<F>>, CheckError> {
+        if witness.len() < self.num_variables {
+            return Err(CheckError::WitnessLength {
+                expected: self.num_variables,
+                found: witness.len(),
+            });
+        }
+
+        let eval = |constraint: usize, lc: &[(usize, F)]| {
+            lc.iter().try_fold(F::ZERO, |acc, &(wire, coeff)| {
+                let value = witness.get(wire).ok_or(CheckError::WireOutOfRange {
+                    constraint,
+                    wire,
+                    wires: witness.len(),
+                })?;
+                Ok(acc + coeff * value)
+            })
+        };
+
+        let mut unsatisfied = vec![];
+        for (index, (a, b, c)) in self.constraints.iter().enumerate() {
+            let (a_val, b_val, c_val) = (eval(index, a)?, eval(index, b)?, eval(index, c)?);
+            if a_val * b_val == c_val {
+                continue;
+            }
+            let signals = sym
+                .map(|sym| {
+                    let mut wires = a.iter().chain(b).chain(c).map(|(w, _)| *w).collect_vec();
+                    wires.sort_unstable();
+                    wires.dedup();
+                    wires
+                        .into_iter()
+                        .filter_map(|w| sym.by_wire(w).map(|s| s.name.clone()))
+                        .collect()
+                })
+                .unwrap_or_default();
+            unsatisfied.push(UnsatisfiedConstraint {
+                index,
+                a: a_val,
+                b: b_val,
+                c: c_val,
+                signals,
+            });
+        }
+        Ok(unsatisfied)
+    }
+
+    /// Converts a wire-indexed witness into a label-indexed one. Labels that were optimized
+    /// away by the compiler, and therefore have no wire, are `None`.
+    pub fn labels_from_wires(&self, witness: &[F]) -> Vec<Option<F>> {
+        let mut labels = vec![None; self.num_labels];
+        for (wire, &label) in self.wire_mapping.iter().enumerate() {
+            if let (Some(l), Some(w)) = (labels.get_mut(label), witness.get(wire)) {
+                *l = Some(*w);
+            }
+        }
+        labels
+    }
+}
+
+/// Errors that prevent checking a witness against an r1cs.
+#[derive(Debug, thiserror::Error)]
+pub enum CheckError {
+    #[error("Witness has {found} elements, expected {expected}")]
+    WitnessLength { expected: usize, found: usize },
+    #[error("Constraint {constraint} references wire {wire}, but the witness has {wires} wires")]
+    WireOutOfRange {
+        constraint: usize,
+        wire: usize,
+        wires: usize,
+    },
+}
+
+/// A constraint `A·w * B·w = C·w` that is not satisfied by a witness.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnsatisfiedConstraint<F: PrimeField> {
+    pub index: usize,
+    /// Value of A·w.
+    pub a: F,
+    /// Value of B·w.
+    pub b: F,
+    /// Value of C·w.
+    pub c: F,
+    /// Names of the signals involved in the constraint, if a symbol table was provided.
+    pub signals: Vec<String>,
+}
+
+impl<F: PrimeField> fmt::Display for UnsatisfiedConstraint<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "constraint {} is not satisfied: A = {}, B = {}, C = {}",
+            self.index,
+            to_decimal_string(&self.a),
+            to_decimal_string(&self.b),
+            to_decimal_string(&self.c)
+        )?;
+        if !self.signals.is_empty() {
+            write!(f, " (signals: {})", self.signals.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// A public signal of a synthesized Circom circuit, possibly an array.
+#[derive(Clone, Debug)]
+pub struct CircomSignal<F: PrimeField> {
+    /// Fully qualified name of the signal, without indices, e.g. `main.out`.
+    pub name: String,
+    /// Dimensions of the signal, empty for a scalar.
+    pub shape: Vec<usize>,
+    /// Elements of the signal, flattened in row-major order.
+    pub values: Vec<AllocatedNum<F>>,
+}
+
+/// An element of an array signal, with its indices.
+type IndexedNum<F> = (Vec<usize>, AllocatedNum<F>);
+
+/// The public outputs and public inputs of a synthesized Circom circuit, see
+/// [`crate::synthesize_with_signals`].
+#[derive(Clone, Debug)]
+pub struct PublicSignals<F: PrimeField> {
+    /// Public outputs, in wire order.
+    pub outputs: Vec<AllocatedNum<F>>,
+    /// Public inputs, in wire order.
+    pub inputs: Vec<AllocatedNum<F>>,
+    /// Public outputs grouped by signal, empty without a symbol table.
+    pub named_outputs: Vec<CircomSignal<F>>,
+    /// Public inputs grouped by signal, empty without a symbol table.
+    pub named_inputs: Vec<CircomSignal<F>>,
+}
+
+impl<F: PrimeField> PublicSignals<F> {
+    /// Collects the public signals out of the allocated wires, starting from wire 1.
+    pub(crate) fn new(r1cs: &R1CS<F>, sym: Option<&SymbolTable>, vars: &[AllocatedNum<F>]) -> Self {
+        let outputs = vars[..r1cs.num_pub_out].to_vec();
+        let inputs = vars[r1cs.num_pub_out..r1cs.num_inputs - 1].to_vec();
+
+        let group = |first_wire: usize, nums: &[AllocatedNum<F>]| {
+            let Some(sym) = sym else {
+                return vec![];
+            };
+            let mut signals: Vec<(String, Vec<IndexedNum<F>>)> = vec![];
+            for (i, num) in nums.iter().enumerate() {
+                let Some(symbol) = sym.by_wire(first_wire + i) else {
+                    continue;
+                };
+                let (name, indices) = (symbol.base_name(), symbol.indices());
+                match signals.last_mut() {
+                    Some((last, elements)) if last == name => elements.push((indices, num.clone())),
+                    _ => signals.push((name.to_string(), vec![(indices, num.clone())])),
+                }
+            }
+            signals
+                .into_iter()
+                .map(|(name, mut elements)| {
+                    elements.sort_by(|(a, _), (b, _)| a.cmp(b));
+                    let shape = shape(elements.iter().map(|(indices, _)| indices.as_slice()));
+                    CircomSignal {
+                        name,
+                        shape,
+                        values: elements.into_iter().map(|(_, num)| num).collect(),
+                    }
+                })
+                .collect()
+        };
+
+        Self {
+            named_outputs: group(1, &outputs),
+            named_inputs: group(1 + r1cs.num_pub_out, &inputs),
+            outputs,
+            inputs,
+        }
+    }
+
+    /// Looks up a public output or public input by its fully qualified name, e.g. `main.out`.
+    pub fn get(&self, name: &str) -> Option<&CircomSignal<F>> {
+        self.named_outputs
+            .iter()
+            .chain(&self.named_inputs)
+            .find(|s| s.name == name)
+    }
+}
+
+/// Dimensions of an array signal, given the indices of its elements.
+fn shape<'a>(elements: impl Iterator<Item = &'a [usize]>) -> Vec<usize> {
+    let mut shape: Vec<usize> = vec![];
+    for indices in elements {
+        shape.resize(shape.len().max(indices.len()), 0);
+        for (dim, index) in shape.iter_mut().zip(indices) {
+            *dim = (*dim).max(index + 1);
+        }
+    }
+    shape
+}
+
+/// An input signal of the main component of a Circom circuit, see
+/// [`CircomConfig::input_signals`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InputSignal {
+    /// Name of the signal, as expected by [`crate::calculate_witness`], e.g. `in`.
+    pub name: String,
+    /// Dimensions of the signal, empty for a scalar.
+    pub shape: Vec<usize>,
+    /// Number of field elements of the signal.
+    pub size: usize,
+    pub public: bool,
+}
+
+impl InputSignal {
+    /// Zero value with the shape of the signal, in the format of Circom's `input.json`.
+    pub fn example(&self) -> serde_json::Value {
+        self.shape
+            .iter()
+            .rev()
+            .fold(serde_json::Value::from("0"), |value, &dim| {
+                serde_json::Value::Array(vec![value; dim])
+            })
+    }
+}
+
+/// Errors that can occur while parsing the input of a witness generator.
+#[derive(Debug, thiserror::Error)]
+pub enum InputError {
+    #[error("Invalid json: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("The input should be a json object mapping signal names to their values")]
+    NotAnObject,
+    #[error("{path} is not a field element: {value}")]
+    NotAFieldElement { path: String, value: String },
+    #[error("{path} mixes buses and field elements")]
+    MixedTypes { path: String },
+}
+
+/// Errors of [`crate::calculate_witness_from_json`]: either the input could not be parsed, or the
+/// witness could not be calculated.
+#[derive(Debug, thiserror::Error)]
+pub enum JsonWitnessError {
+    #[error(transparent)]
+    Input(#[from] InputError),
+    #[error(transparent)]
+    Witness(#[from] WitnessError),
+}
+
+/// The input of a witness generator, parsed from Circom's `input.json` format.
+///
+/// Arrays of any depth are flattened. Field elements are given as json numbers or as decimal,
+/// `0x` hexadecimal, `0o` octal or `0b` binary strings, and are reduced modulo the prime, so that
+/// negative values are accepted. Objects are Circom 2.2 buses, whose fields are given to the
+/// witness generator as the signals `bus.field`, or `bus[i].field` for arrays of buses, as
+/// snarkjs does.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircomInput<F: PrimeField> {
+    /// Flattened values of each signal, as expected by [`crate::calculate_witness`].
+    pub signals: Vec<(String, Vec<F>)>,
 }
 
-#[derive(Serialize, Deserialize)]
-pub(crate) struct CircomInput {
-    pub(crate) arg_in: Vec<String>,
+impl<F: PrimeField> CircomInput<F> {
+    /// Parses an input from a json value.
+    pub fn from_json(value: &serde_json::Value) -> Result<Self, InputError> {
+        let map = value.as_object().ok_or(InputError::NotAnObject)?;
+        let mut signals = vec![];
+        for (name, value) in map {
+            add_signals(name, value, &mut signals)?;
+        }
+        Ok(Self { signals })
+    }
+
+    /// Parses an input from a reader over an `input.json` file.
+    pub fn from_reader(reader: impl Read) -> Result<Self, InputError> {
+        Self::from_json(&serde_json::from_reader(reader)?)
+    }
+}
+
+impl<F: PrimeField> std::str::FromStr for CircomInput<F> {
+    type Err = InputError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_json(&serde_json::from_str(s)?)
+    }
+}
+
+/// Adds the signals of the value at `path`, one per bus field and one for any other value.
+fn add_signals<F: PrimeField>(
+    path: &str,
+    value: &serde_json::Value,
+    signals: &mut Vec<(String, Vec<F>)>,
+) -> Result<(), InputError> {
+    if let serde_json::Value::Object(fields) = value {
+        for (field, value) in fields {
+            add_signals(&format!("{path}.{field}"), value, signals)?;
+        }
+        return Ok(());
+    }
+
+    let mut leaves = vec![];
+    flatten(path.to_string(), value, &mut leaves);
+    if leaves.iter().all(|(_, leaf)| leaf.is_object()) && !leaves.is_empty() {
+        for (path, leaf) in leaves {
+            add_signals(&path, leaf, signals)?;
+        }
+    } else if leaves.iter().any(|(_, leaf)| leaf.is_object()) {
+        return Err(InputError::MixedTypes {
+            path: path.to_string(),
+        });
+    } else {
+        let values = leaves
+            .into_iter()
+            .map(|(path, leaf)| {
+                parse_field(leaf).ok_or_else(|| InputError::NotAFieldElement {
+                    path,
+                    value: leaf.to_string(),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        signals.push((path.to_string(), values));
+    }
+    Ok(())
+}
+
+/// Collects the elements of nested arrays in order, with their paths.
+fn flatten<'a>(
+    path: String,
+    value: &'a serde_json::Value,
+    leaves: &mut Vec<(String, &'a serde_json::Value)>,
+) {
+    match value {
+        serde_json::Value::Array(values) => {
+            for (i, value) in values.iter().enumerate() {
+                flatten(format!("{path}[{i}]"), value, leaves);
+            }
+        }
+        _ => leaves.push((path, value)),
+    }
+}
+
+/// Field element of a json number or string, reduced modulo the prime.
+fn parse_field<F: PrimeField>(value: &serde_json::Value) -> Option<F> {
+    match value {
+        serde_json::Value::Number(n) => match (n.as_u64(), n.as_i64()) {
+            (Some(n), _) => Some(F::from(n)),
+            (None, Some(n)) => Some(-F::from(n.unsigned_abs())),
+            // floats, including integers too large for an i64
+            (None, None) => None,
+        },
+        serde_json::Value::String(s) => {
+            let s = s.trim();
+            let (negative, s) = match s.strip_prefix('-') {
+                Some(abs) => (true, abs),
+                None => (false, s),
+            };
+            let (radix, digits) = match s.get(..2) {
+                Some("0x" | "0X") => (16, &s[2..]),
+                Some("0o" | "0O") => (8, &s[2..]),
+                Some("0b" | "0B") => (2, &s[2..]),
+                _ => (10, s),
+            };
+            // like JavaScript's BigInt, only decimal numbers can be negative
+            if digits.is_empty() || (negative && radix != 10) {
+                return None;
+            }
+            let abs = digits.chars().try_fold(F::ZERO, |acc, c| {
+                let digit = c.to_digit(radix)?;
+                Some(acc * F::from(u64::from(radix)) + F::from(u64::from(digit)))
+            })?;
+            Some(if negative { -abs } else { abs })
+        }
+        _ => None,
+    }
 }
 
 pub(crate) type Constraint<F> = (Vec<(usize, F)>, Vec<(usize, F)>, Vec<(usize, F)>);
@@ -44,16 +511,122 @@ pub struct CircomConfig<F: PrimeField> {
     pub r1cs: R1CS<F>,
     pub wtns: Mutex<WitnessCalculator>,
     pub sanity_check: bool,
+    pub sym: Option<SymbolTable>,
+    /// Additional witness generator instances used by [`crate::calculate_witnesses`].
+    pub pool: Option<WitnessCalculatorPool>,
 }
 
 impl<F: PrimeField> CircomConfig<F> {
-    pub fn new(wtns: impl AsRef<Path>, r1cs: impl AsRef<Path>) -> io::Result<Self> {
-        let wtns = Mutex::new(WitnessCalculator::new(wtns).unwrap());
-        let r1cs = load_r1cs(r1cs);
+    /// Loads the witness generator and the r1cs of a circuit, checking that both were compiled
+    /// for the field `F` and agree on the number of variables.
+    pub fn new(wtns: impl AsRef<Path>, r1cs: impl AsRef<Path>) -> Result<Self> {
+        Self::from_calculator(WitnessCalculator::new(wtns)?, r1cs)
+    }
+
+    /// Same as [`Self::new`], reusing the compiled witness generator cached in `cache_dir`, see
+    /// [`WitnessCalculator::from_file_cached`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn new_cached(
+        wtns: impl AsRef<Path>,
+        r1cs: impl AsRef<Path>,
+        cache_dir: impl AsRef<Path>,
+    ) -> Result<Self> {
+        Self::from_calculator(WitnessCalculator::from_file_cached(wtns, cache_dir)?, r1cs)
+    }
+
+    fn from_calculator(mut wtns: WitnessCalculator, r1cs: impl AsRef<Path>) -> Result<Self> {
+        let r1cs = try_load_r1cs(r1cs)?;
+
+        wtns.check_prime::<F>()?;
+        let witness_size = wtns.witness_size()?;
+        if witness_size != r1cs.num_variables {
+            return Err(WitnessError::WitnessSizeMismatch {
+                expected: r1cs.num_variables,
+                found: witness_size,
+            }
+            .into());
+        }
+
+        let wtns = Mutex::new(wtns);
         Ok(Self {
             wtns,
             r1cs,
             sanity_check: false,
+            sym: None,
+            pool: None,
         })
     }
+
+    /// Checks a witness against the r1cs, see [`R1CS::check`]. Signal names are reported when a
+    /// symbol table is attached.
+    pub fn check(&self, witness: &[F]) -> Result<Vec<UnsatisfiedConstraint<F>>, CheckError> {
+        self.r1cs.check_constraints(witness, self.sym.as_ref())
+    }
+
+    /// Input signals of the main component, public ones first, as declared in the symbol table.
+    /// Returns an empty list when no symbol table is attached, in which case the size of a known
+    /// signal can still be queried with [`WitnessCalculator::input_signal_size`].
+    pub fn input_signals(&self) -> Vec<InputSignal> {
+        let Some(sym) = &self.sym else {
+            return vec![];
+        };
+        let first_input = 1 + self.r1cs.num_pub_out;
+        let first_private = first_input + self.r1cs.num_pub_in;
+        let inputs = first_input..first_private + self.r1cs.num_prv_in;
+
+        let mut signals: Vec<(&str, usize, Vec<Vec<usize>>)> = vec![];
+        for symbol in sym.component_signals("main") {
+            let Some(wire) = symbol.wire.filter(|w| inputs.contains(w)) else {
+                continue;
+            };
+            let name = &symbol.base_name()["main.".len()..];
+            match signals.iter_mut().find(|(n, _, _)| *n == name) {
+                Some((_, first_wire, elements)) => {
+                    *first_wire = (*first_wire).min(wire);
+                    elements.push(symbol.indices());
+                }
+                None => signals.push((name, wire, vec![symbol.indices()])),
+            }
+        }
+        signals.sort_by_key(|(_, first_wire, _)| *first_wire);
+
+        signals
+            .into_iter()
+            .map(|(name, first_wire, elements)| InputSignal {
+                name: name.to_string(),
+                shape: shape(elements.iter().map(Vec::as_slice)),
+                size: elements.len(),
+                public: first_wire < first_private,
+            })
+            .collect()
+    }
+
+    /// Example `input.json` content for the circuit, with every input signal set to zero. Empty
+    /// when no symbol table is attached, see [`Self::input_signals`].
+    pub fn input_json_skeleton(&self) -> serde_json::Value {
+        serde_json::Value::Object(
+            self.input_signals()
+                .iter()
+                .map(|signal| (signal.name.clone(), signal.example()))
+                .collect(),
+        )
+    }
+
+    /// Instantiates the witness generator `size` more times, so that [`crate::calculate_witnesses`]
+    /// calculates up to `size` witnesses in parallel.
+    pub fn with_pool(mut self, size: usize) -> Result<Self, WitnessError> {
+        let calculator = self
+            .wtns
+            .lock()
+            .map_err(|_| WitnessError::LockPoisoned)?
+            .try_clone()?;
+        self.pool = Some(WitnessCalculatorPool::new(calculator, size)?);
+        Ok(self)
+    }
+
+    /// Attaches the symbol table of the circuit, loaded from the `.sym` file emitted by Circom.
+    pub fn with_sym(mut self, sym: impl AsRef<Path>) -> Result<Self, ReaderError> {
+        self.sym = Some(SymbolTable::from_file(sym)?);
+        Ok(self)
+    }
 }
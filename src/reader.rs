@@ -8,7 +8,6 @@
 //   - Adapted the original work here: https://github.com/nalinbhardwaj/Nova-Scotia/blob/main/src/circom/reader.rs
 
 use anyhow::bail;
-use crypto_bigint::U256;
 use ff::PrimeField;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
@@ -16,24 +15,83 @@ use std::collections::HashMap;
 use std::fs::File;
 use std::fs::OpenOptions;
 use std::io::{BufReader, Read, Seek, SeekFrom};
-use std::io::{Error, ErrorKind};
 use std::path::Path;
 
 use byteorder::{LittleEndian, ReadBytesExt};
-use itertools::Itertools;
 
+use crate::field::CircomField;
 use crate::r1cs::Constraint;
 use crate::r1cs::R1CS;
+use crate::writer::{field_size, le_bytes_to_hex, modulus_le_bytes};
+
+/// Errors that can occur while loading an R1CS or a symbol file.
+#[derive(Debug, thiserror::Error)]
+pub enum ReaderError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid json: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid magic number {found:?}, expected {expected:?}")]
+    BadMagic { expected: [u8; 4], found: [u8; 4] },
+    #[error("Unsupported version {0}")]
+    UnsupportedVersion(u32),
+    #[error("Missing section of type {0}")]
+    MissingSection(u32),
+    #[error("Mismatched prime field. Expected {expected}, read {found} in the header instead.")]
+    PrimeMismatch { expected: String, found: String },
+    #[error(
+        "Invalid size for section of type {section}: expected {expected} bytes, found {found}"
+    )]
+    BadSectionSize {
+        section: u32,
+        expected: u64,
+        found: u64,
+    },
+    #[error("Invalid field element {0}")]
+    BadFieldElement(String),
+    #[error(
+        "Field elements of {found} bytes do not fit the target field, which uses {expected} bytes"
+    )]
+    UnsupportedFieldSize { expected: usize, found: u32 },
+    #[error("Section of type {0} extends past the end of the file")]
+    TruncatedSection(u32),
+    #[error(
+        "Inconsistent header: {wires} wires cannot hold the constant one and {public} public signals"
+    )]
+    TooFewWires { wires: usize, public: usize },
+    #[error("Constraint {constraint} should have 3 linear combinations, found {found}")]
+    BadConstraint { constraint: usize, found: usize },
+    #[error("Constraint {constraint} references wire {wire}, but the circuit has {wires} wires")]
+    WireOutOfRange {
+        constraint: usize,
+        wire: usize,
+        wires: usize,
+    },
+    #[error("Wire 0 should always be mapped to 0")]
+    BadWireMapping,
+    #[error("Invalid symbol on line {line}: {content}")]
+    InvalidSymbol { line: usize, content: String },
+}
+
+const HEADER_TYPE: u32 = 1;
+const CONSTRAINT_TYPE: u32 = 2;
+const WIRE2LABEL_TYPE: u32 = 3;
 
 #[derive(Serialize, Deserialize)]
 pub(crate) struct CircuitJson {
-    constraints: Vec<Vec<BTreeMap<String, String>>>,
+    constraints: Vec<Vec<BTreeMap<usize, String>>>,
     #[serde(rename = "nPubInputs")]
     num_inputs: usize,
     #[serde(rename = "nOutputs")]
     num_outputs: usize,
     #[serde(rename = "nVars")]
     num_variables: usize,
+    #[serde(rename = "nPrvInputs", default)]
+    num_prv_inputs: usize,
+    #[serde(rename = "nLabels", default)]
+    num_labels: Option<usize>,
+    #[serde(default)]
+    map: Option<Vec<usize>>,
 }
 
 // R1CSFile's header
@@ -62,7 +120,11 @@ pub struct R1CSFile<F: PrimeField> {
 
 /// load witness file by filename with autodetect encoding (bin or json).
 pub(crate) fn load_witness_from_file<Fr: PrimeField>(filename: impl AsRef<Path>) -> Vec<Fr> {
-    if filename.as_ref().ends_with("json") {
+    if filename
+        .as_ref()
+        .extension()
+        .is_some_and(|ext| ext == "json")
+    {
         load_witness_from_json_file::<Fr>(filename)
     } else {
         load_witness_from_bin_file::<Fr>(filename)
@@ -79,8 +141,8 @@ fn load_witness_from_bin_file<Fr: PrimeField>(filename: impl AsRef<Path>) -> Vec
         .expect("read witness failed")
 }
 
-/// load witness from u8 array by a reader
-fn load_witness_from_bin_reader<Fr: PrimeField, R: Read>(
+/// load witness in the iden3 `.wtns` binary format from a reader
+pub fn load_witness_from_bin_reader<Fr: PrimeField, R: Read>(
     mut reader: R,
 ) -> Result<Vec<Fr>, anyhow::Error> {
     let mut wtns_header = [0u8; 4];
@@ -104,18 +166,18 @@ fn load_witness_from_bin_reader<Fr: PrimeField, R: Read>(
         bail!("invalid section type");
     }
     let sec_size = reader.read_u64::<LittleEndian>()?;
-    if sec_size != 4 + 32 + 4 {
-        bail!("invalid section len")
-    }
     let field_size = reader.read_u32::<LittleEndian>()?;
-    if field_size != 32 {
+    if !fits_field::<Fr>(field_size) {
         bail!("invalid field byte size");
     }
+    if sec_size != 4 + u64::from(field_size) + 4 {
+        bail!("invalid section len")
+    }
     let mut prime = vec![0u8; field_size as usize];
     reader.read_exact(&mut prime)?;
-    // if prime != hex!("010000f093f5e1439170b97948e833285d588181b64550b829a031e1724e6430") {
-    //     bail!("invalid curve prime {:?}", prime);
-    // }
+    if prime != modulus_le_bytes::<Fr>(field_size as usize) {
+        bail!("invalid curve prime {}", le_bytes_to_hex(&prime));
+    }
     let witness_len = reader.read_u32::<LittleEndian>()?;
     //println!("witness len {}", witness_len);
     let sec_type = reader.read_u32::<LittleEndian>()?;
@@ -123,12 +185,12 @@ fn load_witness_from_bin_reader<Fr: PrimeField, R: Read>(
         bail!("invalid section type");
     }
     let sec_size = reader.read_u64::<LittleEndian>()?;
-    if sec_size != u64::from(witness_len * field_size) {
+    if sec_size != u64::from(witness_len) * u64::from(field_size) {
         bail!("invalid witness section size {}", sec_size);
     }
     let mut result = Vec::with_capacity(witness_len as usize);
     for _ in 0..witness_len {
-        result.push(read_field::<&mut R, Fr>(&mut reader)?);
+        result.push(read_field::<&mut R, Fr>(&mut reader, field_size)?);
     }
     Ok(result)
 }
@@ -152,45 +214,50 @@ fn load_witness_from_json<Fr: PrimeField, R: Read>(reader: R) -> Vec<Fr> {
 }
 
 /// load r1cs from bin file by filename
-fn load_r1cs_from_bin_file<F: PrimeField>(filename: impl AsRef<Path>) -> R1CS<F> {
-    let reader = OpenOptions::new()
-        .read(true)
-        .open(filename.as_ref())
-        .unwrap_or_else(|_| panic!("unable to open {:?}", filename.as_ref()));
+fn load_r1cs_from_bin_file<F: PrimeField>(
+    filename: impl AsRef<Path>,
+) -> Result<R1CS<F>, ReaderError> {
+    let reader = OpenOptions::new().read(true).open(filename.as_ref())?;
     load_r1cs_from_bin(BufReader::new(reader))
 }
 
-fn read_field<R: Read, Fr: PrimeField>(mut reader: R) -> Result<Fr, Error> {
-    let mut repr = Fr::ZERO.to_repr();
-    for digit in repr.as_mut().iter_mut() {
-        // TODO: may need to reverse order?
-        *digit = reader.read_u8()?;
-    }
-    let fr = Fr::from_repr(repr).unwrap();
-    Ok(fr)
+/// Whether field elements encoded on `size` bytes can hold every element of `Fr`.
+fn fits_field<Fr: PrimeField>(size: u32) -> bool {
+    u64::from(size) * 8 >= u64::from(Fr::NUM_BITS)
+}
+
+/// Reads a field element encoded on `size` little endian bytes, which may be shorter or longer
+/// than `Fr::Repr`.
+fn read_field<R: Read, Fr: PrimeField>(mut reader: R, size: u32) -> Result<Fr, ReaderError> {
+    let mut bytes = vec![0u8; size as usize];
+    reader.read_exact(&mut bytes)?;
+    Fr::from_le_bytes(&bytes).ok_or_else(|| ReaderError::BadFieldElement(le_bytes_to_hex(&bytes)))
 }
 
-fn read_header<R: Read>(mut reader: R, size: u64, expected_prime: &str) -> Result<Header, Error> {
+fn read_header<R: Read, Fr: PrimeField>(mut reader: R, size: u64) -> Result<Header, ReaderError> {
     let field_size = reader.read_u32::<LittleEndian>()?;
 
     if size != 32 + u64::from(field_size) {
-        return Err(Error::new(
-            ErrorKind::InvalidData,
-            "Invalid header section size",
-        ));
+        return Err(ReaderError::BadSectionSize {
+            section: HEADER_TYPE,
+            expected: 32 + u64::from(field_size),
+            found: size,
+        });
     }
 
     let mut prime_size = vec![0u8; field_size as usize];
     reader.read_exact(&mut prime_size)?;
-    let prime = U256::from_le_slice(&prime_size);
-    let prime = &prime.to_string().to_ascii_lowercase();
-
-    if prime != &expected_prime[2..] {
-        // get rid of '0x' in the front
-        return Err(Error::new(
-            ErrorKind::InvalidData,
-            format!("Mismatched prime field. Expected {expected_prime}, read {prime} in the header instead."),
-        ));
+    if !fits_field::<Fr>(field_size) {
+        return Err(ReaderError::UnsupportedFieldSize {
+            expected: crate::writer::field_size::<Fr>(),
+            found: field_size,
+        });
+    }
+    if prime_size != modulus_le_bytes::<Fr>(field_size as usize) {
+        return Err(ReaderError::PrimeMismatch {
+            expected: Fr::MODULUS.to_string(),
+            found: le_bytes_to_hex(&prime_size),
+        });
     }
 
     Ok(Header {
@@ -207,70 +274,96 @@ fn read_header<R: Read>(mut reader: R, size: u64, expected_prime: &str) -> Resul
 
 fn read_constraint_vec<R: Read, Fr: PrimeField>(
     mut reader: R,
-    _header: &Header,
-) -> Result<Vec<(usize, Fr)>, Error> {
-    let n_vec = reader.read_u32::<LittleEndian>()? as usize;
-    let mut vec = Vec::with_capacity(n_vec);
+    size: u64,
+    header: &Header,
+    constraint: usize,
+) -> Result<Vec<(usize, Fr)>, ReaderError> {
+    let n_vec = reader.read_u32::<LittleEndian>()?;
+    // each term takes 4 + field_size bytes of the section, don't trust n_vec beyond that
+    let term_size = 4 + u64::from(header.field_size);
+    let mut vec = Vec::with_capacity(u64::from(n_vec).min(size / term_size) as usize);
     for _ in 0..n_vec {
+        let wire = reader.read_u32::<LittleEndian>()? as usize;
+        if wire >= header.n_wires as usize {
+            return Err(ReaderError::WireOutOfRange {
+                constraint,
+                wire,
+                wires: header.n_wires as usize,
+            });
+        }
         vec.push((
-            reader.read_u32::<LittleEndian>()? as usize,
-            read_field::<&mut R, Fr>(&mut reader)?,
+            wire,
+            read_field::<&mut R, Fr>(&mut reader, header.field_size)?,
         ));
     }
     Ok(vec)
 }
 
-fn read_constraints<R: Read, Fr: PrimeField>(
+fn read_constraints<R: Read + Seek, Fr: PrimeField>(
     mut reader: R,
-    _size: u64,
+    size: u64,
     header: &Header,
-) -> Result<Vec<Constraint<Fr>>, Error> {
-    // todo check section size
-    let mut vec = Vec::with_capacity(header.n_constraints as usize);
-    for _ in 0..header.n_constraints {
+) -> Result<Vec<Constraint<Fr>>, ReaderError> {
+    let start = reader.stream_position()?;
+    // a constraint takes at least 12 bytes, for the lengths of its linear combinations
+    let mut vec = Vec::with_capacity(u64::from(header.n_constraints).min(size / 12) as usize);
+    for i in 0..header.n_constraints as usize {
         vec.push((
-            read_constraint_vec::<&mut R, Fr>(&mut reader, header)?,
-            read_constraint_vec::<&mut R, Fr>(&mut reader, header)?,
-            read_constraint_vec::<&mut R, Fr>(&mut reader, header)?,
+            read_constraint_vec::<&mut R, Fr>(&mut reader, size, header, i)?,
+            read_constraint_vec::<&mut R, Fr>(&mut reader, size, header, i)?,
+            read_constraint_vec::<&mut R, Fr>(&mut reader, size, header, i)?,
         ));
     }
+    let read = reader.stream_position()? - start;
+    if read != size {
+        return Err(ReaderError::BadSectionSize {
+            section: CONSTRAINT_TYPE,
+            expected: read,
+            found: size,
+        });
+    }
     Ok(vec)
 }
 
-fn read_map<R: Read>(mut reader: R, size: u64, header: &Header) -> Result<Vec<u64>, Error> {
+fn read_map<R: Read>(mut reader: R, size: u64, header: &Header) -> Result<Vec<u64>, ReaderError> {
     if size != u64::from(header.n_wires) * 8 {
-        return Err(Error::new(
-            ErrorKind::InvalidData,
-            "Invalid map section size",
-        ));
+        return Err(ReaderError::BadSectionSize {
+            section: WIRE2LABEL_TYPE,
+            expected: u64::from(header.n_wires) * 8,
+            found: size,
+        });
     }
     let mut vec = Vec::with_capacity(header.n_wires as usize);
     for _ in 0..header.n_wires {
         vec.push(reader.read_u64::<LittleEndian>()?);
     }
-    if vec[0] != 0 {
-        return Err(Error::new(
-            ErrorKind::InvalidData,
-            "Wire 0 should always be mapped to 0",
-        ));
+    if vec.first() != Some(&0) {
+        return Err(ReaderError::BadWireMapping);
     }
     Ok(vec)
 }
 
-fn from_reader<Fr: PrimeField, R: Read + Seek>(mut reader: R) -> Result<R1CSFile<Fr>, Error> {
+fn from_reader<Fr: PrimeField, R: Read + Seek>(mut reader: R) -> Result<R1CSFile<Fr>, ReaderError> {
     let mut magic = [0u8; 4];
     reader.read_exact(&mut magic)?;
-    if magic != [0x72, 0x31, 0x63, 0x73] {
-        // magic = "r1cs"
-        return Err(Error::new(ErrorKind::InvalidData, "Invalid magic number"));
+    // magic = "r1cs"
+    let expected = [0x72, 0x31, 0x63, 0x73];
+    if magic != expected {
+        return Err(ReaderError::BadMagic {
+            expected,
+            found: magic,
+        });
     }
 
     let version = reader.read_u32::<LittleEndian>()?;
     if version != 1 {
-        return Err(Error::new(ErrorKind::InvalidData, "Unsupported version"));
+        return Err(ReaderError::UnsupportedVersion(version));
     }
 
     let num_sections = reader.read_u32::<LittleEndian>()?;
+    let start = reader.stream_position()?;
+    let len = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(start))?;
 
     // section type -> file offset
     let mut section_offsets = HashMap::<u32, u64>::new();
@@ -281,48 +374,35 @@ fn from_reader<Fr: PrimeField, R: Read + Seek>(mut reader: R) -> Result<R1CSFile
         let section_type = reader.read_u32::<LittleEndian>()?;
         let section_size = reader.read_u64::<LittleEndian>()?;
         let offset = reader.stream_position()?;
+        // the sizes read below are bounded by the section sizes, which are bounded by the file
+        if section_size > len - offset {
+            return Err(ReaderError::TruncatedSection(section_type));
+        }
         section_offsets.insert(section_type, offset);
         section_sizes.insert(section_type, section_size);
-        reader.seek(SeekFrom::Current(section_size as i64))?;
+        reader.seek(SeekFrom::Start(offset + section_size))?;
     }
 
-    let header_type = 1;
-    let constraint_type = 2;
-    let wire2label_type = 3;
-
-    reader.seek(SeekFrom::Start(*section_offsets.get(&header_type).unwrap()))?;
-    let header = read_header(
-        &mut reader,
-        *section_sizes.get(&header_type).unwrap(),
-        Fr::MODULUS,
-    )?;
-    if header.field_size != 32 {
-        return Err(Error::new(
-            ErrorKind::InvalidData,
-            "This parser only supports 32-byte fields",
-        ));
-    }
+    // seek to the start of a section and return its size
+    let seek_section = |reader: &mut R, section_type: u32| -> Result<u64, ReaderError> {
+        let offset = section_offsets
+            .get(&section_type)
+            .ok_or(ReaderError::MissingSection(section_type))?;
+        reader.seek(SeekFrom::Start(*offset))?;
+        Ok(section_sizes[&section_type])
+    };
+
+    let size = seek_section(&mut reader, HEADER_TYPE)?;
+    let header = read_header::<_, Fr>(&mut reader, size)?;
     // if header.prime_size != hex!("010000f093f5e1439170b97948e833285d588181b64550b829a031e1724e6430") {
     //     return Err(Error::new(ErrorKind::InvalidData, "This parser only supports bn256"));
     // }
 
-    reader.seek(SeekFrom::Start(
-        *section_offsets.get(&constraint_type).unwrap(),
-    ))?;
-    let constraints = read_constraints::<&mut R, Fr>(
-        &mut reader,
-        *section_sizes.get(&constraint_type).unwrap(),
-        &header,
-    )?;
-
-    reader.seek(SeekFrom::Start(
-        *section_offsets.get(&wire2label_type).unwrap(),
-    ))?;
-    let wire_mapping = read_map(
-        &mut reader,
-        *section_sizes.get(&wire2label_type).unwrap(),
-        &header,
-    )?;
+    let size = seek_section(&mut reader, CONSTRAINT_TYPE)?;
+    let constraints = read_constraints::<&mut R, Fr>(&mut reader, size, &header)?;
+
+    let size = seek_section(&mut reader, WIRE2LABEL_TYPE)?;
+    let wire_mapping = read_map(&mut reader, size, &header)?;
 
     Ok(R1CSFile {
         version,
@@ -333,22 +413,47 @@ fn from_reader<Fr: PrimeField, R: Read + Seek>(mut reader: R) -> Result<R1CSFile
 }
 
 /// load r1cs from bin by a reader
-fn load_r1cs_from_bin<Fr: PrimeField, R: Read + Seek>(reader: R) -> R1CS<Fr> {
-    let file = from_reader(reader).expect("unable to read.");
-    let num_inputs = (1 + file.header.n_pub_in + file.header.n_pub_out) as usize;
+pub(crate) fn load_r1cs_from_bin<Fr: PrimeField, R: Read + Seek>(
+    reader: R,
+) -> Result<R1CS<Fr>, ReaderError> {
+    let file = from_reader(reader)?;
+    let num_inputs = 1 + file.header.n_pub_in as usize + file.header.n_pub_out as usize;
     let num_variables = file.header.n_wires as usize;
-    let num_aux = num_variables - num_inputs;
-    R1CS {
+    let num_aux = num_variables
+        .checked_sub(num_inputs)
+        .ok_or(ReaderError::TooFewWires {
+            wires: num_variables,
+            public: num_inputs - 1,
+        })?;
+    Ok(R1CS {
+        field_size: file.header.field_size as usize,
+        prime: file.header.prime_size,
         num_aux,
         num_inputs,
         num_variables,
+        num_pub_out: file.header.n_pub_out as usize,
+        num_pub_in: file.header.n_pub_in as usize,
+        num_prv_in: file.header.n_prv_in as usize,
+        num_labels: file.header.n_labels as usize,
         constraints: file.constraints,
-    }
+        wire_mapping: file.wire_mapping.into_iter().map(|l| l as usize).collect(),
+    })
 }
 
 /// load r1cs file by filename with autodetect encoding (bin or json)
+///
+/// Panics if the file cannot be loaded, see [`try_load_r1cs`] for a fallible version.
 pub fn load_r1cs<Fr: PrimeField>(filename: impl AsRef<Path>) -> R1CS<Fr> {
-    if filename.as_ref().ends_with("json") {
+    try_load_r1cs(filename).expect("unable to load r1cs")
+}
+
+/// load r1cs file by filename with autodetect encoding (bin or json)
+pub fn try_load_r1cs<Fr: PrimeField>(filename: impl AsRef<Path>) -> Result<R1CS<Fr>, ReaderError> {
+    if filename
+        .as_ref()
+        .extension()
+        .is_some_and(|ext| ext == "json")
+    {
         load_r1cs_from_json_file(filename)
     } else {
         load_r1cs_from_bin_file(filename)
@@ -356,43 +461,79 @@ pub fn load_r1cs<Fr: PrimeField>(filename: impl AsRef<Path>) -> R1CS<Fr> {
 }
 
 /// load r1cs from json file by filename
-fn load_r1cs_from_json_file<Fr: PrimeField>(filename: impl AsRef<Path>) -> R1CS<Fr> {
-    let reader = OpenOptions::new()
-        .read(true)
-        .open(filename)
-        .expect("unable to open.");
+fn load_r1cs_from_json_file<Fr: PrimeField>(
+    filename: impl AsRef<Path>,
+) -> Result<R1CS<Fr>, ReaderError> {
+    let reader = OpenOptions::new().read(true).open(filename)?;
     load_r1cs_from_json(BufReader::new(reader))
 }
 
 /// load r1cs from json by a reader
-fn load_r1cs_from_json<Fr: PrimeField, R: Read>(reader: R) -> R1CS<Fr> {
-    let circuit_json: CircuitJson = serde_json::from_reader(reader).expect("unable to read.");
-
-    let num_inputs = circuit_json.num_inputs + circuit_json.num_outputs + 1;
-    let num_aux = circuit_json.num_variables - num_inputs;
-
-    let convert_constraint = |lc: &BTreeMap<String, String>| {
+fn load_r1cs_from_json<Fr: PrimeField, R: Read>(reader: R) -> Result<R1CS<Fr>, ReaderError> {
+    let circuit_json: CircuitJson = serde_json::from_reader(reader)?;
+
+    let public = circuit_json
+        .num_inputs
+        .saturating_add(circuit_json.num_outputs);
+    let num_inputs = public.saturating_add(1);
+    let num_aux =
+        circuit_json
+            .num_variables
+            .checked_sub(num_inputs)
+            .ok_or(ReaderError::TooFewWires {
+                wires: circuit_json.num_variables,
+                public,
+            })?;
+
+    let convert_constraint = |constraint: usize, lc: &BTreeMap<usize, String>| {
         lc.iter()
-            .map(|(index, coeff)| (index.parse().unwrap(), Fr::from_str_vartime(coeff).unwrap()))
-            .collect_vec()
+            .map(|(index, coeff)| {
+                if *index >= circuit_json.num_variables {
+                    return Err(ReaderError::WireOutOfRange {
+                        constraint,
+                        wire: *index,
+                        wires: circuit_json.num_variables,
+                    });
+                }
+                let coeff = Fr::from_str_vartime(coeff)
+                    .ok_or_else(|| ReaderError::BadFieldElement(coeff.clone()))?;
+                Ok((*index, coeff))
+            })
+            .collect::<Result<Vec<_>, ReaderError>>()
     };
 
     let constraints = circuit_json
         .constraints
         .iter()
-        .map(|c| {
-            (
-                convert_constraint(&c[0]),
-                convert_constraint(&c[1]),
-                convert_constraint(&c[2]),
-            )
+        .enumerate()
+        .map(|(i, c)| match c.as_slice() {
+            [a, b, c] => Ok((
+                convert_constraint(i, a)?,
+                convert_constraint(i, b)?,
+                convert_constraint(i, c)?,
+            )),
+            _ => Err(ReaderError::BadConstraint {
+                constraint: i,
+                found: c.len(),
+            }),
         })
-        .collect_vec();
+        .collect::<Result<Vec<_>, ReaderError>>()?;
 
-    R1CS {
+    Ok(R1CS {
+        field_size: field_size::<Fr>(),
+        prime: modulus_le_bytes::<Fr>(field_size::<Fr>()),
         num_inputs,
         num_aux,
         num_variables: circuit_json.num_variables,
+        num_pub_out: circuit_json.num_outputs,
+        num_pub_in: circuit_json.num_inputs,
+        num_prv_in: circuit_json.num_prv_inputs,
+        num_labels: circuit_json
+            .num_labels
+            .unwrap_or(circuit_json.num_variables),
         constraints,
-    }
+        wire_mapping: circuit_json
+            .map
+            .unwrap_or_else(|| (0..circuit_json.num_variables).collect()),
+    })
 }
@@ -0,0 +1,165 @@
+// Copyright (c) Lurk Lab
+// SPDX-License-Identifier: MIT
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use crate::reader::ReaderError;
+
+/// A signal declared in a Circom `.sym` file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Symbol {
+    pub label: usize,
+    /// Wire index of the signal in the r1cs, `None` if the signal was optimized away.
+    pub wire: Option<usize>,
+    pub component: usize,
+    /// Fully qualified name of the signal, e.g. `main.sha.out[3]`.
+    pub name: String,
+}
+
+impl Symbol {
+    /// Name of the signal without its array indices, e.g. `main.sha.out` for `main.sha.out[3]`.
+    pub fn base_name(&self) -> &str {
+        let last_segment = self.name.rfind('.').map_or(0, |i| i + 1);
+        match self.name[last_segment..].find('[') {
+            Some(i) => &self.name[..last_segment + i],
+            None => &self.name,
+        }
+    }
+
+    /// Array indices of the signal, e.g. `[1, 2]` for `main.out[1][2]`, empty for a scalar.
+    pub fn indices(&self) -> Vec<usize> {
+        self.name[self.base_name().len()..]
+            .split(['[', ']'])
+            .filter_map(|i| i.parse().ok())
+            .collect()
+    }
+}
+
+/// The signals of a circuit, as listed by the `.sym` file emitted by Circom.
+#[derive(Clone, Debug, Default)]
+pub struct SymbolTable {
+    symbols: Vec<Symbol>,
+    by_name: HashMap<String, usize>,
+    by_wire: HashMap<usize, usize>,
+}
+
+impl SymbolTable {
+    /// load a symbol table from a `.sym` file by filename
+    pub fn from_file(filename: impl AsRef<Path>) -> Result<Self, ReaderError> {
+        Self::from_reader(BufReader::new(File::open(filename)?))
+    }
+
+    /// load a symbol table from a reader
+    ///
+    /// Each line is of the form `label,wire,component,name`, where `wire` is `-1` for signals
+    /// that do not appear in the r1cs.
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self, ReaderError> {
+        let mut symbols = Vec::new();
+        let mut by_name = HashMap::new();
+        let mut by_wire = HashMap::new();
+
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let invalid = || ReaderError::InvalidSymbol {
+                line: i + 1,
+                content: line.clone(),
+            };
+
+            let mut fields = line.splitn(4, ',');
+            let mut next_int = || -> Result<i64, ReaderError> {
+                fields
+                    .next()
+                    .and_then(|f| f.trim().parse().ok())
+                    .ok_or_else(invalid)
+            };
+            let label = next_int()?;
+            let wire = next_int()?;
+            let component = next_int()?;
+            let name = fields.next().ok_or_else(invalid)?.trim().to_string();
+            if label < 0 || component < 0 || wire < -1 {
+                return Err(invalid());
+            }
+
+            let wire = usize::try_from(wire).ok();
+            by_name.insert(name.clone(), symbols.len());
+            if let Some(wire) = wire {
+                by_wire.entry(wire).or_insert(symbols.len());
+            }
+            symbols.push(Symbol {
+                label: label as usize,
+                wire,
+                component: component as usize,
+                name,
+            });
+        }
+
+        Ok(Self {
+            symbols,
+            by_name,
+            by_wire,
+        })
+    }
+
+    /// All the symbols, in the order of the `.sym` file.
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    /// Looks up a signal by its fully qualified name.
+    pub fn get(&self, name: &str) -> Option<&Symbol> {
+        self.by_name.get(name).map(|&i| &self.symbols[i])
+    }
+
+    /// Looks up the first signal declared on a wire.
+    pub fn by_wire(&self, wire: usize) -> Option<&Symbol> {
+        self.by_wire.get(&wire).map(|&i| &self.symbols[i])
+    }
+
+    /// Wire index of a signal, `None` if it is unknown or was optimized away.
+    pub fn wire(&self, name: &str) -> Option<usize> {
+        self.get(name).and_then(|s| s.wire)
+    }
+
+    /// Wire indices of all the elements of a possibly multidimensional signal, given by its fully
+    /// qualified name without indices (e.g. `main.in`), in row-major order. Returns `None` if the
+    /// signal is unknown or one of its elements was optimized away.
+    pub fn signal_wires(&self, name: &str) -> Option<Vec<usize>> {
+        let mut elements = self
+            .symbols
+            .iter()
+            .filter(|s| s.base_name() == name)
+            .map(|s| (s.indices(), s.wire))
+            .collect::<Vec<_>>();
+        if elements.is_empty() {
+            return None;
+        }
+        elements.sort_by(|(a, _), (b, _)| a.cmp(b));
+        elements.into_iter().map(|(_, wire)| wire).collect()
+    }
+
+    /// Value of a signal in a wire-indexed witness, such as the one returned by
+    /// [`crate::calculate_witness`].
+    pub fn value<F: Copy>(&self, witness: &[F], name: &str) -> Option<F> {
+        self.wire(name).and_then(|w| witness.get(w).copied())
+    }
+
+    /// Signals declared directly by a component, given by its fully qualified name (e.g.
+    /// `main.sha`). Signals of its subcomponents are not included.
+    pub fn component_signals<'a>(
+        &'a self,
+        component: &'a str,
+    ) -> impl Iterator<Item = &'a Symbol> + 'a {
+        self.symbols.iter().filter(move |s| {
+            s.name
+                .strip_prefix(component)
+                .and_then(|rest| rest.strip_prefix('.'))
+                .is_some_and(|signal| !signal.contains('.'))
+        })
+    }
+}
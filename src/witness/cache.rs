@@ -0,0 +1,104 @@
+// Copyright (c) Lurk Lab
+// SPDX-License-Identifier: MIT
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use sha2::{Digest, Sha256};
+use wasmer::{IoCompileError, Module, NativeEngineExt, Store};
+
+use super::WitnessError;
+
+/// Extension of the cached modules.
+const EXTENSION: &str = "wasmu";
+
+/// Number of temporary files created by this process, to name them uniquely across threads.
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Compiles the wasm file at `path`, or deserializes it from `cache_dir` if it was compiled by an
+/// earlier run with the same engine. Fresh compilations are written to the cache on a best effort
+/// basis, failing to do so is not an error.
+///
+/// Entries are keyed by the hash of the wasm and the wasmer version, compiler and target, and
+/// prefixed by the hash of their content. Entries that are corrupt or rejected by the engine are
+/// compiled again and overwritten.
+pub(crate) fn load_or_compile(
+    store: &Store,
+    path: impl AsRef<Path>,
+    cache_dir: impl AsRef<Path>,
+) -> Result<Module, WitnessError> {
+    let wasm = fs::read(path).map_err(IoCompileError::Io)?;
+    let entry = cache_dir
+        .as_ref()
+        .join(format!("{}.{EXTENSION}", cache_key(store, &wasm)));
+
+    if let Some(module) = load(store, &entry) {
+        return Ok(module);
+    }
+
+    let module = Module::new(store, &wasm).map_err(IoCompileError::Compile)?;
+    let _ = save(&module, &entry);
+    Ok(module)
+}
+
+fn cache_key(store: &Store, wasm: &[u8]) -> String {
+    let engine = store.engine();
+    let mut hasher = Sha256::new();
+    hasher.update(wasm);
+    for part in [
+        wasmer::VERSION,
+        engine.deterministic_id(),
+        &engine.target().triple().to_string(),
+    ] {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part);
+    }
+    to_hex(&hasher.finalize())
+}
+
+/// Reads a cache entry, `None` if it is missing, corrupt or stale.
+fn load(store: &Store, entry: &Path) -> Option<Module> {
+    let bytes = fs::read(entry).ok()?;
+    if bytes.len() < Sha256::output_size() {
+        return None;
+    }
+    let (checksum, module) = bytes.split_at(Sha256::output_size());
+    if Sha256::digest(module).as_slice() != checksum {
+        return None;
+    }
+    // SAFETY: the entry was written by `save` for this engine, as checked by the key and the
+    // checksum, and the archive is validated again by `Module::deserialize`.
+    unsafe { Module::deserialize(store, module.to_vec()) }.ok()
+}
+
+/// Writes a cache entry through a temporary file, so that concurrent readers never see a
+/// partially written entry.
+fn save(module: &Module, entry: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let serialized = module.serialize()?;
+    let dir = entry.parent().ok_or("cache entry without a directory")?;
+    fs::create_dir_all(dir)?;
+
+    let tmp = PathBuf::from(format!(
+        "{}.{}.{}.tmp",
+        entry.display(),
+        std::process::id(),
+        TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let written = (|| {
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(&Sha256::digest(&serialized))?;
+        file.write_all(&serialized)?;
+        file.sync_all()?;
+        fs::rename(&tmp, entry)
+    })();
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    Ok(written?)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
@@ -8,17 +8,23 @@
 //   - Adapted the original work here: https://github.com/arkworks-rs/circom-compat/blob/master/src/witness/circom.rs
 
 use color_eyre::Result;
+#[cfg(feature = "circom-2")]
+use std::fmt;
 use wasmer::{AsStoreMut, Function, Instance, Value};
+#[cfg(feature = "circom-2")]
+use wasmer::{Memory, TypedFunction, WasmTypeList};
+
+use super::WitnessError;
 
 #[derive(Clone, Debug)]
 pub struct Wasm(Instance);
 
 pub trait CircomBase {
-    fn init(&self, store: &mut impl AsStoreMut, sanity_check: bool) -> Result<()>;
-    fn func(&self, name: &str) -> &Function;
-    fn get_ptr_witness_buffer(&self, store: &mut impl AsStoreMut) -> Result<u32>;
-    fn get_ptr_witness(&self, store: &mut impl AsStoreMut, w: u32) -> Result<u32>;
-    fn get_n_vars(&self, store: &mut impl AsStoreMut) -> Result<u32>;
+    fn init(&self, store: &mut impl AsStoreMut, sanity_check: bool) -> Result<(), WitnessError>;
+    fn func(&self, name: &str) -> Result<&Function, WitnessError>;
+    fn get_ptr_witness_buffer(&self, store: &mut impl AsStoreMut) -> Result<u32, WitnessError>;
+    fn get_ptr_witness(&self, store: &mut impl AsStoreMut, w: u32) -> Result<u32, WitnessError>;
+    fn get_n_vars(&self, store: &mut impl AsStoreMut) -> Result<u32, WitnessError>;
     fn get_signal_offset32(
         &self,
         store: &mut impl AsStoreMut,
@@ -26,7 +32,7 @@ pub trait CircomBase {
         component: u32,
         hash_msb: u32,
         hash_lsb: u32,
-    ) -> Result<()>;
+    ) -> Result<(), WitnessError>;
     fn set_signal(
         &self,
         store: &mut impl AsStoreMut,
@@ -34,109 +40,116 @@ pub trait CircomBase {
         component: u32,
         signal: u32,
         p_val: u32,
-    ) -> Result<()>;
-    fn get_u32(&self, store: &mut impl AsStoreMut, name: &str) -> Result<u32>;
+    ) -> Result<(), WitnessError>;
+    fn get_u32(&self, store: &mut impl AsStoreMut, name: &str) -> Result<u32, WitnessError>;
     // Only exists natively in Circom2, hardcoded for Circom
-    fn get_version(&self, store: &mut impl AsStoreMut) -> Result<u32>;
+    fn get_version(&self, store: &mut impl AsStoreMut) -> Result<u32, WitnessError>;
 }
 
 pub trait Circom {
-    fn get_fr_len(&self, store: &mut impl AsStoreMut) -> Result<u32>;
-    fn get_ptr_raw_prime(&self, store: &mut impl AsStoreMut) -> Result<u32>;
+    fn get_fr_len(&self, store: &mut impl AsStoreMut) -> Result<u32, WitnessError>;
+    fn get_ptr_raw_prime(&self, store: &mut impl AsStoreMut) -> Result<u32, WitnessError>;
 }
 
+#[cfg(feature = "circom-2")]
 pub trait Circom2 {
-    fn get_field_num_len32(&self, store: &mut impl AsStoreMut) -> Result<u32>;
-    fn get_raw_prime(&self, store: &mut impl AsStoreMut) -> Result<()>;
-    fn read_shared_rw_memory(&self, store: &mut impl AsStoreMut, i: u32) -> Result<u32>;
-    fn write_shared_rw_memory(&self, store: &mut impl AsStoreMut, i: u32, v: u32) -> Result<()>;
-    fn set_input_signal(
+    fn get_field_num_len32(&self, store: &mut impl AsStoreMut) -> Result<u32, WitnessError>;
+    fn get_raw_prime(&self, store: &mut impl AsStoreMut) -> Result<(), WitnessError>;
+    fn read_shared_rw_memory(
+        &self,
+        store: &mut impl AsStoreMut,
+        i: u32,
+    ) -> Result<u32, WitnessError>;
+    fn get_witness_size(&self, store: &mut impl AsStoreMut) -> Result<u32, WitnessError>;
+    // Only exported since Circom 2.1
+    fn get_input_signal_size(
         &self,
         store: &mut impl AsStoreMut,
         hmsb: u32,
         hlsb: u32,
-        pos: u32,
-    ) -> Result<()>;
-    fn get_witness(&self, store: &mut impl AsStoreMut, i: u32) -> Result<()>;
-    fn get_witness_size(&self, store: &mut impl AsStoreMut) -> Result<u32>;
+    ) -> Result<Option<usize>, WitnessError>;
+    fn get_input_size(&self, store: &mut impl AsStoreMut) -> Result<u32, WitnessError>;
 }
 
 impl Circom for Wasm {
-    fn get_fr_len(&self, store: &mut impl AsStoreMut) -> Result<u32> {
+    fn get_fr_len(&self, store: &mut impl AsStoreMut) -> Result<u32, WitnessError> {
         self.get_u32(store, "getFrLen")
     }
 
-    fn get_ptr_raw_prime(&self, store: &mut impl AsStoreMut) -> Result<u32> {
+    fn get_ptr_raw_prime(&self, store: &mut impl AsStoreMut) -> Result<u32, WitnessError> {
         self.get_u32(store, "getPRawPrime")
     }
 }
 
 #[cfg(feature = "circom-2")]
 impl Circom2 for Wasm {
-    fn get_field_num_len32(&self, store: &mut impl AsStoreMut) -> Result<u32> {
+    fn get_field_num_len32(&self, store: &mut impl AsStoreMut) -> Result<u32, WitnessError> {
         self.get_u32(store, "getFieldNumLen32")
     }
 
-    fn get_raw_prime(&self, store: &mut impl AsStoreMut) -> Result<()> {
-        let func = self.func("getRawPrime");
+    fn get_raw_prime(&self, store: &mut impl AsStoreMut) -> Result<(), WitnessError> {
+        let func = self.func("getRawPrime")?;
         func.call(store, &[])?;
         Ok(())
     }
 
-    fn read_shared_rw_memory(&self, store: &mut impl AsStoreMut, i: u32) -> Result<u32> {
-        let func = self.func("readSharedRWMemory");
+    fn read_shared_rw_memory(
+        &self,
+        store: &mut impl AsStoreMut,
+        i: u32,
+    ) -> Result<u32, WitnessError> {
+        let func = self.func("readSharedRWMemory")?;
         let result = func.call(store, &[i.into()])?;
-        Ok(result[0].unwrap_i32() as u32)
+        return_u32("readSharedRWMemory", &result)
     }
 
-    fn write_shared_rw_memory(&self, store: &mut impl AsStoreMut, i: u32, v: u32) -> Result<()> {
-        let func = self.func("writeSharedRWMemory");
-        func.call(store, &[i.into(), v.into()])?;
-        Ok(())
+    fn get_witness_size(&self, store: &mut impl AsStoreMut) -> Result<u32, WitnessError> {
+        self.get_u32(store, "getWitnessSize")
     }
 
-    fn set_input_signal(
+    // Unknown signals have a null or negative size, depending on the Circom version
+    fn get_input_signal_size(
         &self,
         store: &mut impl AsStoreMut,
         hmsb: u32,
         hlsb: u32,
-        pos: u32,
-    ) -> Result<()> {
-        let func = self.func("setInputSignal");
-        func.call(store, &[hmsb.into(), hlsb.into(), pos.into()])?;
-        Ok(())
-    }
-
-    fn get_witness(&self, store: &mut impl AsStoreMut, i: u32) -> Result<()> {
-        let func = self.func("getWitness");
-        func.call(store, &[i.into()])?;
-        Ok(())
+    ) -> Result<Option<usize>, WitnessError> {
+        let func = self.input_func("getInputSignalSize")?;
+        let result = func.call(store, &[hmsb.into(), hlsb.into()])?;
+        match result.as_ref() {
+            [Value::I32(v)] => Ok(usize::try_from(*v).ok().filter(|&size| size > 0)),
+            _ => Err(WitnessError::InvalidReturnType(
+                "getInputSignalSize".to_string(),
+            )),
+        }
     }
 
-    fn get_witness_size(&self, store: &mut impl AsStoreMut) -> Result<u32> {
-        self.get_u32(store, "getWitnessSize")
+    fn get_input_size(&self, store: &mut impl AsStoreMut) -> Result<u32, WitnessError> {
+        let func = self.input_func("getInputSize")?;
+        let result = func.call(store, &[])?;
+        return_u32("getInputSize", &result)
     }
 }
 
 impl CircomBase for Wasm {
-    fn init(&self, store: &mut impl AsStoreMut, sanity_check: bool) -> Result<()> {
-        let func = self.func("init");
+    fn init(&self, store: &mut impl AsStoreMut, sanity_check: bool) -> Result<(), WitnessError> {
+        let func = self.func("init")?;
         func.call(store, &[Value::I32(i32::from(sanity_check))])?;
         Ok(())
     }
 
-    fn get_ptr_witness_buffer(&self, store: &mut impl AsStoreMut) -> Result<u32> {
+    fn get_ptr_witness_buffer(&self, store: &mut impl AsStoreMut) -> Result<u32, WitnessError> {
         self.get_u32(store, "getWitnessBuffer")
     }
 
-    fn get_ptr_witness(&self, store: &mut impl AsStoreMut, w: u32) -> Result<u32> {
-        let func = self.func("getPWitness");
+    fn get_ptr_witness(&self, store: &mut impl AsStoreMut, w: u32) -> Result<u32, WitnessError> {
+        let func = self.func("getPWitness")?;
         let res = func.call(store, &[w.into()])?;
 
-        Ok(res[0].unwrap_i32() as u32)
+        return_u32("getPWitness", &res)
     }
 
-    fn get_n_vars(&self, store: &mut impl AsStoreMut) -> Result<u32> {
+    fn get_n_vars(&self, store: &mut impl AsStoreMut) -> Result<u32, WitnessError> {
         self.get_u32(store, "getNVars")
     }
 
@@ -147,8 +160,8 @@ impl CircomBase for Wasm {
         component: u32,
         hash_msb: u32,
         hash_lsb: u32,
-    ) -> Result<()> {
-        let func = self.func("getSignalOffset32");
+    ) -> Result<(), WitnessError> {
+        let func = self.func("getSignalOffset32")?;
         func.call(
             store,
             &[
@@ -169,8 +182,8 @@ impl CircomBase for Wasm {
         component: u32,
         signal: u32,
         p_val: u32,
-    ) -> Result<()> {
-        let func = self.func("setSignal");
+    ) -> Result<(), WitnessError> {
+        let func = self.func("setSignal")?;
         func.call(
             store,
             &[c_idx.into(), component.into(), signal.into(), p_val.into()],
@@ -180,24 +193,24 @@ impl CircomBase for Wasm {
     }
 
     // Default to version 1 if it isn't explicitly defined
-    fn get_version(&self, store: &mut impl AsStoreMut) -> Result<u32> {
+    fn get_version(&self, store: &mut impl AsStoreMut) -> Result<u32, WitnessError> {
         match self.0.exports.get_function("getVersion") {
-            Ok(func) => Ok(func.call(store, &[])?[0].unwrap_i32() as u32),
+            Ok(func) => return_u32("getVersion", &func.call(store, &[])?),
             Err(_) => Ok(1),
         }
     }
 
-    fn get_u32(&self, store: &mut impl AsStoreMut, name: &str) -> Result<u32> {
-        let func = self.func(name);
+    fn get_u32(&self, store: &mut impl AsStoreMut, name: &str) -> Result<u32, WitnessError> {
+        let func = self.func(name)?;
         let result = func.call(store, &[])?;
-        Ok(result[0].unwrap_i32() as u32)
+        return_u32(name, &result)
     }
 
-    fn func(&self, name: &str) -> &Function {
+    fn func(&self, name: &str) -> Result<&Function, WitnessError> {
         self.0
             .exports
             .get_function(name)
-            .unwrap_or_else(|_| panic!("function {} not found", name))
+            .map_err(|_| WitnessError::MissingExport(name.to_string()))
     }
 }
 
@@ -205,4 +218,153 @@ impl Wasm {
     pub fn new(instance: Instance) -> Self {
         Self(instance)
     }
+
+    // The inputs of the main component are only described since Circom 2.1
+    #[cfg(feature = "circom-2")]
+    fn input_func(&self, name: &str) -> Result<&Function, WitnessError> {
+        self.func(name)
+            .map_err(|_| WitnessError::RequiresCircom21(name.to_string()))
+    }
+
+    #[cfg(feature = "circom-2")]
+    fn typed<Args: WasmTypeList, Rets: WasmTypeList>(
+        &self,
+        store: &impl AsStoreMut,
+        name: &str,
+    ) -> Result<TypedFunction<Args, Rets>, WitnessError> {
+        self.func(name)?
+            .typed(store)
+            .map_err(|_| WitnessError::InvalidReturnType(name.to_string()))
+    }
+}
+
+/// The exports of a Circom 2 witness generator used to set the inputs and read the witness,
+/// resolved once.
+///
+/// Field elements go through the shared memory of the runtime. When the witness generator
+/// exports its memory and the location of the shared memory, they are copied in bulk instead of
+/// one call per 32 bits limb.
+#[cfg(feature = "circom-2")]
+#[derive(Clone)]
+pub(crate) struct Circom2Io {
+    n32: usize,
+    set_input_signal: TypedFunction<(u32, u32, u32), ()>,
+    get_witness: TypedFunction<u32, ()>,
+    read_shared_rw_memory: TypedFunction<u32, u32>,
+    write_shared_rw_memory: TypedFunction<(u32, u32), ()>,
+    /// Exported memory and offset of the shared memory in it.
+    shared_rw_memory: Option<(Memory, u64)>,
+}
+
+#[cfg(feature = "circom-2")]
+impl fmt::Debug for Circom2Io {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Circom2Io")
+            .field("n32", &self.n32)
+            .field("shared_rw_memory", &self.shared_rw_memory)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "circom-2")]
+impl Circom2Io {
+    pub(crate) fn new(instance: &Wasm, store: &mut impl AsStoreMut) -> Result<Self, WitnessError> {
+        let n32 = instance.get_field_num_len32(store)? as usize;
+
+        let memory = instance.0.exports.get_memory("memory").ok().cloned();
+        let shared_rw_memory = match (memory, instance.func("getSharedRWMemoryStart")) {
+            (Some(memory), Ok(_)) => {
+                let start = instance.get_u32(store, "getSharedRWMemoryStart")?;
+                Some((memory, u64::from(start)))
+            }
+            _ => None,
+        };
+
+        Ok(Self {
+            n32,
+            set_input_signal: instance.typed(store, "setInputSignal")?,
+            get_witness: instance.typed(store, "getWitness")?,
+            read_shared_rw_memory: instance.typed(store, "readSharedRWMemory")?,
+            write_shared_rw_memory: instance.typed(store, "writeSharedRWMemory")?,
+            shared_rw_memory,
+        })
+    }
+
+    /// Number of 32 bits limbs of a field element.
+    pub(crate) fn n32(&self) -> usize {
+        self.n32
+    }
+
+    /// Writes the little endian limbs of a field element to the shared memory.
+    pub(crate) fn write_shared(
+        &self,
+        store: &mut impl AsStoreMut,
+        limbs: &[u32],
+    ) -> Result<(), WitnessError> {
+        match &self.shared_rw_memory {
+            Some((memory, start)) => {
+                let bytes = limbs
+                    .iter()
+                    .flat_map(|limb| limb.to_le_bytes())
+                    .collect::<Vec<_>>();
+                memory.view(store).write(*start, &bytes)?;
+            }
+            None => {
+                for (i, &limb) in limbs.iter().enumerate() {
+                    self.write_shared_rw_memory.call(store, i as u32, limb)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the little endian limbs of the field element in the shared memory.
+    pub(crate) fn read_shared(
+        &self,
+        store: &mut impl AsStoreMut,
+        limbs: &mut [u32],
+    ) -> Result<(), WitnessError> {
+        match &self.shared_rw_memory {
+            Some((memory, start)) => {
+                let mut bytes = vec![0u8; limbs.len() * 4];
+                memory.view(store).read(*start, &mut bytes)?;
+                for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks_exact(4)) {
+                    *limb = u32::from_le_bytes(chunk.try_into().unwrap());
+                }
+            }
+            None => {
+                for (i, limb) in limbs.iter_mut().enumerate() {
+                    *limb = self.read_shared_rw_memory.call(store, i as u32)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn set_input_signal(
+        &self,
+        store: &mut impl AsStoreMut,
+        hmsb: u32,
+        hlsb: u32,
+        pos: u32,
+    ) -> Result<(), WitnessError> {
+        Ok(self.set_input_signal.call(store, hmsb, hlsb, pos)?)
+    }
+
+    /// Copies the `i`-th witness element to the shared memory.
+    pub(crate) fn get_witness(
+        &self,
+        store: &mut impl AsStoreMut,
+        i: u32,
+    ) -> Result<(), WitnessError> {
+        Ok(self.get_witness.call(store, i)?)
+    }
+}
+
+/// Reads the single `i32` returned by an export as a `u32`.
+fn return_u32(name: &str, result: &[Value]) -> Result<u32, WitnessError> {
+    match result {
+        [Value::I32(v)] => Ok(*v as u32),
+        _ => Err(WitnessError::InvalidReturnType(name.to_string())),
+    }
 }
@@ -0,0 +1,100 @@
+// Copyright (c) Lurk Lab
+// SPDX-License-Identifier: MIT
+
+use wasmer::{InstantiationError, IoCompileError, MemoryAccessError, MemoryError, RuntimeError};
+
+/// Errors that can occur while loading a witness generator or calculating a witness.
+#[derive(Debug, thiserror::Error)]
+pub enum WitnessError {
+    #[error("Missing export {0} in the witness generator")]
+    MissingExport(String),
+    #[error("Export {0} returned an unexpected value")]
+    InvalidReturnType(String),
+    #[error("Unsupported Circom runtime version {0}")]
+    UnsupportedVersion(u32),
+    #[error(
+        "The witness generator does not export {0}, it must be compiled with Circom 2.1 or later"
+    )]
+    RequiresCircom21(String),
+    #[error("Mismatched prime field. Expected {expected}, the witness generator uses {found}")]
+    PrimeMismatch { expected: String, found: String },
+    #[error(
+        "The witness generator computes {found} witness values, the r1cs has {expected} variables"
+    )]
+    WitnessSizeMismatch { expected: usize, found: usize },
+    #[error("Unknown input signal {0}")]
+    UnknownSignal(String),
+    #[error("Input signal {name} has {expected} elements, {found} given")]
+    WrongInputLength {
+        name: String,
+        expected: usize,
+        found: usize,
+    },
+    #[error("Not all inputs have been set, only {found} out of {expected}")]
+    MissingInputs { found: usize, expected: usize },
+    #[error("Assertion failed in the circuit: {0}")]
+    AssertionFailure(String),
+    #[error("Circom runtime exception {code}: {message}")]
+    Exception { code: u32, message: String },
+    #[error("Wasm trap: {0}")]
+    Trap(RuntimeError),
+    #[error("Unable to compile the witness generator: {0}")]
+    Compile(#[from] IoCompileError),
+    #[error("Unable to instantiate the witness generator: {0}")]
+    Instantiation(Box<InstantiationError>),
+    #[error("Unable to allocate the witness generator memory: {0}")]
+    Memory(#[from] MemoryError),
+    #[error("Out of bounds access to the witness generator memory: {0}")]
+    MemoryAccess(#[from] MemoryAccessError),
+    #[error("The witness calculator lock is poisoned")]
+    LockPoisoned,
+}
+
+impl WitnessError {
+    /// Builds the error for an exception raised by the Circom 2 runtime through
+    /// `exceptionHandler`, given the messages it printed before raising it.
+    ///
+    /// See https://github.com/iden3/circom/blob/master/code_producers/src/wasm_elements/common/witness_calculator.js
+    pub(crate) fn from_exception(code: u32, trace: &str) -> Self {
+        let trace = trace.trim_end();
+        if code == 4 {
+            let message = if trace.is_empty() {
+                "Assert Failed"
+            } else {
+                trace
+            };
+            return Self::AssertionFailure(message.to_string());
+        }
+
+        let message = match code {
+            1 => "Signal not found",
+            2 => "Too many signals set",
+            3 => "Signal already set",
+            5 => "Not enough memory",
+            6 => "Input signal array access exceeds the size",
+            _ => "Unknown error",
+        };
+        let message = if trace.is_empty() {
+            message.to_string()
+        } else {
+            format!("{message}. {trace}")
+        };
+        Self::Exception { code, message }
+    }
+}
+
+impl From<RuntimeError> for WitnessError {
+    fn from(err: RuntimeError) -> Self {
+        // host functions stop the execution by raising the error to report
+        match err.downcast::<WitnessError>() {
+            Ok(err) => err,
+            Err(err) => Self::Trap(err),
+        }
+    }
+}
+
+impl From<InstantiationError> for WitnessError {
+    fn from(err: InstantiationError) -> Self {
+        Self::Instantiation(Box::new(err))
+    }
+}
@@ -8,22 +8,31 @@
 //   - Adapted the original work here: https://github.com/arkworks-rs/circom-compat/blob/master/src/witness/memory.rs
 //   - Retrofitted for support without `arkworks` libraries such as `ark-ff` or `ark-bignum`, which were replaced with `ff` and `crypto-bignum`.
 
-use crypto_bigint::{Encoding, U256};
 use ff::PrimeField;
-use wasmer::{AsStoreRef, Memory, MemoryView};
+use wasmer::{AsStoreRef, Memory, MemoryAccessError, MemoryView};
 
 use color_eyre::Result;
+
+use super::WitnessError;
 use std::ops::Deref;
 
-use super::witness_calculator::{from_vec_u32, u256_to_vec_u32};
+use super::witness_calculator::from_le_limbs_reduced;
+use crate::field::CircomField;
+use crate::writer::limbs_to_decimal_string;
+
+/// Flag set in the type word of a field element stored in the long form.
+const LONG: u32 = 0x8000_0000;
+/// Flag set in the type word of a long field element stored in Montgomery form.
+const MONTGOMERY: u32 = 0x4000_0000;
 
+/// The memory of a Circom 1 witness generator, where each field element is stored as an `i32`
+/// short value, a `u32` type word and `n32` little endian `u32` limbs used in the long form.
 #[derive(Clone, Debug)]
 pub struct SafeMemory {
     pub memory: Memory,
-    pub prime: U256,
+    /// Little endian `u32` limbs of the prime.
+    pub prime: Vec<u32>,
 
-    short_max: U256,
-    short_min: U256,
     n32: usize,
 }
 
@@ -37,18 +46,8 @@ impl Deref for SafeMemory {
 
 impl SafeMemory {
     /// Creates a new `SafeMemory`
-    pub fn new(memory: Memory, n32: usize, prime: U256) -> Self {
-        // TODO: Figure out a better way to calculate these
-        let short_max = U256::from(0x8000_0000u64);
-        let short_min = short_max.neg_mod(&prime);
-
-        Self {
-            memory,
-            prime,
-            short_max,
-            short_min,
-            n32,
-        }
+    pub fn new(memory: Memory, n32: usize, prime: Vec<u32>) -> Self {
+        Self { memory, prime, n32 }
     }
 
     /// Gets an immutable view to the memory in 32 byte chunks
@@ -57,101 +56,214 @@ impl SafeMemory {
     }
 
     /// Returns the next free position in the memory
-    pub fn free_pos(&self, store: &impl AsStoreRef) -> u32 {
+    pub fn free_pos(&self, store: &impl AsStoreRef) -> Result<u32, WitnessError> {
         self.read_u32(store, 0)
     }
 
     /// Sets the next free position in the memory
-    pub fn set_free_pos(&mut self, store: &impl AsStoreRef, ptr: u32) {
-        self.write_u32(store, 0, ptr);
+    pub fn set_free_pos(&mut self, store: &impl AsStoreRef, ptr: u32) -> Result<(), WitnessError> {
+        self.write_u32(store, 0, ptr)
     }
 
     /// Allocates a U32 in memory
-    pub fn alloc_u32(&mut self, store: &impl AsStoreRef) -> u32 {
-        let p = self.free_pos(store);
-        self.set_free_pos(store, p + 8);
-        p
+    pub fn alloc_u32(&mut self, store: &impl AsStoreRef) -> Result<u32, WitnessError> {
+        self.alloc(store, 8)
     }
 
     /// Writes a u32 to the specified memory offset
-    pub fn write_u32(&mut self, store: &impl AsStoreRef, ptr: usize, num: u32) {
-        let view = self.view(store);
-        let buf = unsafe { view.data_unchecked_mut() };
-        buf[ptr..ptr + std::mem::size_of::<u32>()].copy_from_slice(&num.to_le_bytes());
+    pub fn write_u32(
+        &mut self,
+        store: &impl AsStoreRef,
+        ptr: usize,
+        num: u32,
+    ) -> Result<(), WitnessError> {
+        self.view(store).write(ptr as u64, &num.to_le_bytes())?;
+        Ok(())
     }
 
     /// Reads a u32 from the specified memory offset
-    pub fn read_u32(&self, store: &impl AsStoreRef, ptr: usize) -> u32 {
-        let view = self.view(store);
-        let buf = unsafe { view.data_unchecked() };
-
+    pub fn read_u32(&self, store: &impl AsStoreRef, ptr: usize) -> Result<u32, WitnessError> {
         let mut bytes = [0; 4];
-        bytes.copy_from_slice(&buf[ptr..ptr + std::mem::size_of::<u32>()]);
-
-        u32::from_le_bytes(bytes)
+        self.view(store).read(ptr as u64, &mut bytes)?;
+        Ok(u32::from_le_bytes(bytes))
     }
 
     /// Allocates `self.n32 * 4 + 8` bytes in the memory
-    pub fn alloc_fr(&mut self, store: &impl AsStoreRef) -> u32 {
-        let p = self.free_pos(store);
-        self.set_free_pos(store, p + self.n32 as u32 * 4 + 8);
-        p
+    pub fn alloc_fr(&mut self, store: &impl AsStoreRef) -> Result<u32, WitnessError> {
+        self.alloc(store, self.n32 as u32 * 4 + 8)
+    }
+
+    fn alloc(&mut self, store: &impl AsStoreRef, size: u32) -> Result<u32, WitnessError> {
+        let p = self.free_pos(store)?;
+        let end = p.checked_add(size).ok_or(MemoryAccessError::Overflow)?;
+        self.set_free_pos(store, end)?;
+        Ok(p)
     }
 
-    /// Writes a Field Element to memory at the specified offset, truncating
-    /// to smaller u32 types if needed and adjusting the sign via 2s complement
-    pub fn write_fr(&mut self, store: &impl AsStoreRef, ptr: usize, fr: U256) -> Result<()> {
-        if fr < self.short_max && fr > self.short_min {
-            self.write_short(store, ptr, fr)?;
+    /// Writes a Field Element to memory at the specified offset, in the short form if it fits
+    /// in an `i32` once centered around zero, in the long normal form otherwise.
+    pub fn write_fr<F: PrimeField>(
+        &mut self,
+        store: &impl AsStoreRef,
+        ptr: usize,
+        fr: F,
+    ) -> Result<(), WitnessError> {
+        if let Some(short) = to_u32(fr).filter(|&v| v <= i32::MAX as u32) {
+            self.write_short(store, ptr, short as i32)
+        } else if let Some(short) = to_u32(-fr).filter(|&v| v <= i32::MIN.unsigned_abs()) {
+            self.write_short(store, ptr, 0i32.wrapping_sub_unsigned(short))
         } else {
-            self.write_long_normal(store, ptr, fr)?;
+            self.write_long_normal(store, ptr, fr)
         }
-
-        Ok(())
     }
 
     /// Reads a Field Element from the memory at the specified offset
-    pub fn read_fr<F: PrimeField>(&self, store: &impl AsStoreRef, ptr: usize) -> F {
-        let view = self.view(store);
-        let view = unsafe { view.data_unchecked_mut() };
+    pub fn read_fr<F: PrimeField>(
+        &self,
+        store: &impl AsStoreRef,
+        ptr: usize,
+    ) -> Result<F, WitnessError> {
+        Ok(from_le_limbs_reduced(&self.read_fr_limbs(store, ptr)?))
+    }
 
-        if view[ptr + 7] & 0x80 != 0 {
-            let num = self.read_big(store, ptr + 8);
-            from_vec_u32(u256_to_vec_u32(num))
+    /// Reads the value of a Field Element from the memory at the specified offset as `n32` little
+    /// endian limbs, out of the Montgomery form.
+    pub(crate) fn read_fr_limbs(
+        &self,
+        store: &impl AsStoreRef,
+        ptr: usize,
+    ) -> Result<Vec<u32>, WitnessError> {
+        let kind = self.read_u32(store, ptr + 4)?;
+
+        if kind & LONG != 0 {
+            let limbs = self.read_big(store, ptr + 8)?;
+            if kind & MONTGOMERY != 0 {
+                Ok(from_montgomery(limbs, &self.prime))
+            } else {
+                Ok(limbs)
+            }
         } else {
-            F::from(u64::from(self.read_u32(store, ptr)))
+            let short = self.read_u32(store, ptr)? as i32;
+            let mut limbs = vec![0; self.n32];
+            limbs[0] = short.unsigned_abs();
+            if short < 0 {
+                Ok(sub(&self.prime, &limbs))
+            } else {
+                Ok(limbs)
+            }
         }
     }
 
-    fn write_short(&mut self, store: &impl AsStoreRef, ptr: usize, fr: U256) -> Result<()> {
-        let num = fr.to_words()[0] as u32;
-        self.write_u32(store, ptr, num);
-        self.write_u32(store, ptr + 4, 0);
-        Ok(())
+    /// Formats a Field Element from the memory like `Fr.toString` in the Circom 1 runtime: in
+    /// decimal, elements above `p / 2` as negative numbers.
+    pub(crate) fn fr_to_string(
+        &self,
+        store: &impl AsStoreRef,
+        ptr: usize,
+    ) -> Result<String, WitnessError> {
+        let limbs = self.read_fr_limbs(store, ptr)?;
+        let neg = sub(&self.prime, &limbs);
+        if less(&neg, &limbs) {
+            Ok(format!("-{}", limbs_to_decimal_string(neg)))
+        } else {
+            Ok(limbs_to_decimal_string(limbs))
+        }
+    }
+
+    fn write_short(
+        &mut self,
+        store: &impl AsStoreRef,
+        ptr: usize,
+        num: i32,
+    ) -> Result<(), WitnessError> {
+        self.write_u32(store, ptr, num as u32)?;
+        self.write_u32(store, ptr + 4, 0)
     }
 
-    fn write_long_normal(&mut self, store: &impl AsStoreRef, ptr: usize, fr: U256) -> Result<()> {
-        self.write_u32(store, ptr, 0);
-        self.write_u32(store, ptr + 4, i32::MIN as u32); // 0x80000000
-        self.write_big(store, ptr + 8, fr)?;
+    fn write_long_normal<F: PrimeField>(
+        &mut self,
+        store: &impl AsStoreRef,
+        ptr: usize,
+        fr: F,
+    ) -> Result<(), WitnessError> {
+        self.write_u32(store, ptr, 0)?;
+        self.write_u32(store, ptr + 4, LONG)?;
+
+        // the bytes past the limbs are zero, as fr is smaller than the prime
+        let mut limbs = fr.to_le_bytes();
+        limbs.resize(self.n32 * 4, 0);
+        self.view(store).write(ptr as u64 + 8, &limbs)?;
         Ok(())
     }
 
-    fn write_big(&self, store: &impl AsStoreRef, ptr: usize, num: U256) -> Result<()> {
-        let view = self.view(store);
-        let buf = unsafe { view.data_unchecked_mut() };
+    /// Reads the `n32` little endian limbs of a big integer from the specified memory offset
+    pub fn read_big(&self, store: &impl AsStoreRef, ptr: usize) -> Result<Vec<u32>, WitnessError> {
+        let mut bytes = vec![0; self.n32 * 4];
+        self.view(store).read(ptr as u64, &mut bytes)?;
+        Ok(bytes
+            .chunks_exact(4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect())
+    }
+}
+
+/// `a < b` on little endian integers of the same length.
+fn less(a: &[u32], b: &[u32]) -> bool {
+    a.iter().rev().cmp(b.iter().rev()).is_lt()
+}
 
-        let bytes: [u8; 32] = num.to_le_bytes();
-        buf[ptr..ptr + 32].copy_from_slice(&bytes);
+/// `a - b` on little endian integers of the same length, wrapping around.
+fn sub(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut borrow = false;
+    a.iter()
+        .zip(b)
+        .map(|(&a, &b)| {
+            let (d, b1) = a.overflowing_sub(b);
+            let (d, b2) = d.overflowing_sub(u32::from(borrow));
+            borrow = b1 || b2;
+            d
+        })
+        .collect()
+}
 
-        Ok(())
+/// `limbs / 2^(32 * n32) mod prime`, by Montgomery reduction.
+fn from_montgomery(limbs: Vec<u32>, prime: &[u32]) -> Vec<u32> {
+    // -1 / prime mod 2^32, by Newton iterations
+    let mut inv = 1u32;
+    for _ in 0..5 {
+        inv = inv.wrapping_mul(2u32.wrapping_sub(prime[0].wrapping_mul(inv)));
     }
+    let inv = inv.wrapping_neg();
 
-    /// Reads `num_bytes * 32` from the specified memory offset in a Big Integer
-    pub fn read_big(&self, store: &impl AsStoreRef, ptr: usize) -> U256 {
-        let view = self.view(store);
-        let buf = unsafe { view.data_unchecked() };
+    let n32 = prime.len();
+    let mut t = limbs;
+    t.resize(n32 + 2, 0);
+    for _ in 0..n32 {
+        // t + m * prime is divisible by 2^32
+        let m = t[0].wrapping_mul(inv);
+        let mut carry = 0u64;
+        for (j, limb) in t.iter_mut().enumerate() {
+            let p = prime.get(j).map_or(0, |&p| u64::from(p));
+            let cur = u64::from(*limb) + u64::from(m) * p + carry;
+            *limb = cur as u32;
+            carry = cur >> 32;
+        }
+        t.remove(0);
+        t.push(0);
+    }
+    t.truncate(n32 + 1);
 
-        U256::from_le_slice(&buf[ptr..])
+    let mut prime = prime.to_vec();
+    prime.push(0);
+    if !less(&t, &prime) {
+        t = sub(&t, &prime);
     }
+    t.truncate(n32);
+    t
+}
+
+/// Value of a field element if it fits in a `u32`.
+fn to_u32<F: PrimeField>(fr: F) -> Option<u32> {
+    let limbs = fr.to_le_limbs();
+    limbs[1..].iter().all(|&limb| limb == 0).then_some(limbs[0])
 }
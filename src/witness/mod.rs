@@ -10,6 +10,15 @@
 mod witness_calculator;
 pub use witness_calculator::WitnessCalculator;
 
+mod error;
+pub use error::WitnessError;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod cache;
+
+mod pool;
+pub use pool::WitnessCalculatorPool;
+
 mod memory;
 pub(super) use memory::SafeMemory;
 
@@ -17,7 +26,7 @@ mod circom;
 pub(super) use circom::{CircomBase, Wasm};
 
 #[cfg(feature = "circom-2")]
-pub(super) use circom::Circom2;
+pub(super) use circom::{Circom2, Circom2Io};
 
 pub(super) use circom::Circom;
 
@@ -0,0 +1,124 @@
+// Copyright (c) Lurk Lab
+// SPDX-License-Identifier: MIT
+
+use std::path::Path;
+use std::sync::{Condvar, Mutex, PoisonError};
+use std::thread;
+
+use ff::PrimeField;
+
+use super::{WitnessCalculator, WitnessError};
+
+/// A fixed set of instances of the same witness generator, compiled once, to calculate several
+/// witnesses concurrently.
+#[derive(Debug)]
+pub struct WitnessCalculatorPool {
+    idle: Mutex<Vec<WitnessCalculator>>,
+    available: Condvar,
+    size: usize,
+}
+
+impl WitnessCalculatorPool {
+    /// Creates a pool of `size` instances of the witness generator of `calculator`, which becomes
+    /// one of them.
+    pub fn new(calculator: WitnessCalculator, size: usize) -> Result<Self, WitnessError> {
+        let size = size.max(1);
+        let mut idle = Vec::with_capacity(size);
+        for _ in 1..size {
+            idle.push(calculator.try_clone()?);
+        }
+        idle.push(calculator);
+
+        Ok(Self {
+            idle: Mutex::new(idle),
+            available: Condvar::new(),
+            size,
+        })
+    }
+
+    /// Compiles the witness generator at `path` and instantiates it `size` times.
+    pub fn from_file(path: impl AsRef<Path>, size: usize) -> Result<Self, WitnessError> {
+        Self::new(WitnessCalculator::from_file(path)?, size)
+    }
+
+    /// Number of instances in the pool, i.e. the maximum number of witnesses calculated at once.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Calculates a witness on the first idle instance, waiting for one if they are all busy.
+    pub fn calculate_witness<F: PrimeField>(
+        &self,
+        input: Vec<(String, Vec<F>)>,
+        sanity_check: bool,
+    ) -> Result<Vec<F>, WitnessError> {
+        let mut calculator = self.checkout()?;
+        let witness = calculator.calculate_witness(input, sanity_check);
+        if witness.is_err() {
+            // a failed run can leave the wasm state half updated, start over from a fresh
+            // instance, keeping the old one if that fails
+            if let Ok(fresh) = calculator.try_clone() {
+                calculator = fresh;
+            }
+        }
+        self.checkin(calculator);
+        witness
+    }
+
+    /// Calculates the witnesses of a batch of inputs on up to [`Self::size`] threads. Results are
+    /// returned in the order of `inputs`.
+    pub fn calculate_witnesses<F, I>(
+        &self,
+        inputs: I,
+        sanity_check: bool,
+    ) -> Vec<Result<Vec<F>, WitnessError>>
+    where
+        F: PrimeField,
+        I: IntoIterator<Item = Vec<(String, Vec<F>)>>,
+        I::IntoIter: Send,
+    {
+        let inputs = Mutex::new(inputs.into_iter().enumerate());
+        let results = Mutex::new(Vec::new());
+
+        thread::scope(|s| {
+            for _ in 0..self.size {
+                s.spawn(|| loop {
+                    let next = inputs.lock().unwrap_or_else(PoisonError::into_inner).next();
+                    let Some((i, input)) = next else {
+                        break;
+                    };
+                    let witness = self.calculate_witness(input, sanity_check);
+                    results
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .push((i, witness));
+                });
+            }
+        });
+
+        let mut results = results.into_inner().unwrap_or_else(PoisonError::into_inner);
+        results.sort_by_key(|(i, _)| *i);
+        results.into_iter().map(|(_, witness)| witness).collect()
+    }
+
+    fn checkout(&self) -> Result<WitnessCalculator, WitnessError> {
+        let mut idle = self.idle.lock().map_err(|_| WitnessError::LockPoisoned)?;
+        loop {
+            if let Some(calculator) = idle.pop() {
+                return Ok(calculator);
+            }
+            idle = self
+                .available
+                .wait(idle)
+                .map_err(|_| WitnessError::LockPoisoned)?;
+        }
+    }
+
+    fn checkin(&self, calculator: WitnessCalculator) {
+        self.idle
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(calculator);
+        self.available.notify_one();
+    }
+}
@@ -8,12 +8,17 @@
 //   - Adapted the original work here: https://github.com/arkworks-rs/circom-compat/blob/master/src/witness/witness_calculator.rs
 //   - Retrofitted for support without `arkworks` libraries such as `ark-ff` or `ark-bignum`, which were replaced with `ff` and `crypto-bignum`.
 
-use super::{fnv, CircomBase, SafeMemory, Wasm};
+#[cfg(not(target_arch = "wasm32"))]
+use super::cache;
+use super::{fnv, CircomBase, SafeMemory, Wasm, WitnessError};
+use crate::field::CircomField;
+use crate::writer::{field_size, le_bytes_to_hex, modulus_le_bytes};
 use color_eyre::Result;
-use crypto_bigint::U256;
 use ff::PrimeField;
+use std::io::Write;
 use wasmer::{
-    imports, AsStoreMut, Function, Instance, Memory, MemoryType, Module, RuntimeError, Store,
+    imports, AsStoreMut, Function, FunctionEnv, FunctionEnvMut, Instance, Memory,
+    MemoryAccessError, MemoryType, Module, RuntimeError, Store,
 };
 
 #[cfg(feature = "llvm")]
@@ -23,7 +28,7 @@ use wasmer_compiler_llvm::LLVM;
 // use num::ToPrimitive;
 
 #[cfg(feature = "circom-2")]
-use super::Circom2;
+use super::{Circom2, Circom2Io};
 
 use super::Circom;
 
@@ -34,105 +39,106 @@ pub struct WitnessCalculator {
     pub memory: SafeMemory,
     pub n64: u32,
     pub circom_version: u32,
+    env: FunctionEnv<runtime::RuntimeEnv>,
+    module: Module,
+    /// Resolved exports of a Circom 2 witness generator.
+    #[cfg(feature = "circom-2")]
+    io: Option<Circom2Io>,
 }
 
-// Error type to signal end of execution.
-// From https://docs.wasmer.io/integrations/examples/exit-early
-#[derive(thiserror::Error, Debug, Clone, Copy)]
-#[error("{0}")]
-struct ExitCode(u32);
-
-/// Little endian
-pub fn from_vec_u32<F: PrimeField>(arr: Vec<u32>) -> F {
-    let mut res = F::ZERO;
-    let radix = F::from(0x0001_0000_0000_u64);
-    for &val in &arr {
-        res = res * radix + F::from(u64::from(val));
-    }
-    res
+/// Field element of the integer with little endian `u32` limbs `limbs`, reduced modulo the prime.
+pub(crate) fn from_le_limbs_reduced<F: PrimeField>(limbs: &[u32]) -> F {
+    F::from_le_limbs(limbs).unwrap_or_else(|| {
+        let radix = F::from(0x0001_0000_0000_u64);
+        limbs
+            .iter()
+            .rev()
+            .fold(F::ZERO, |acc, &limb| acc * radix + F::from(u64::from(limb)))
+    })
 }
 
-/// Little endian
-#[cfg(feature = "circom-2")]
-pub fn to_vec_u32<F: PrimeField>(f: F) -> Vec<u32> {
-    let repr = F::to_repr(&f);
-    let repr = repr.as_ref();
-
-    let (pre, res, suf) = unsafe { repr.align_to::<u32>() };
-    assert_eq!(pre.len(), 0);
-    assert_eq!(suf.len(), 0);
-
-    res.into()
+/// Number of significant bits of a little endian integer.
+fn bits(limbs: &[u32]) -> usize {
+    limbs
+        .iter()
+        .rposition(|&limb| limb != 0)
+        .map_or(0, |i| i * 32 + (32 - limbs[i].leading_zeros() as usize))
 }
 
-/// Little endian
-#[cfg(feature = "circom-2")]
-pub fn u256_from_vec_u32(data: &[u32]) -> U256 {
-    let mut limbs = [0u32; 8];
-    limbs.copy_from_slice(data);
-
+/// Store of the compiler selected by the features of the crate.
+fn new_store() -> Store {
     cfg_if::cfg_if! {
-        if #[cfg(target_pointer_width = "64")] {
-            let (pre, limbs, suf) = unsafe { limbs.align_to::<u64>() };
-            assert_eq!(pre.len(), 0);
-            assert_eq!(suf.len(), 0);
-            U256::from_words(limbs.try_into().unwrap())
+        if #[cfg(feature = "llvm")] {
+            let compiler = LLVM::new();
+            Store::new(compiler)
         } else {
-            U256::from_words(limbs.as_ref().try_into().unwrap())
+            Store::default()
         }
     }
 }
 
-/// Little endian
-pub fn u256_to_vec_u32(s: U256) -> Vec<u32> {
-    let words = s.to_words();
-    let (pre, res, suf) = unsafe { words.align_to::<u32>() };
-    assert_eq!(pre.len(), 0);
-    assert_eq!(suf.len(), 0);
-
-    res.into()
-}
-
 impl WitnessCalculator {
-    pub fn new(path: impl AsRef<std::path::Path>) -> Result<Self> {
+    pub fn new(path: impl AsRef<std::path::Path>) -> Result<Self, WitnessError> {
         Self::from_file(path)
     }
 
-    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self> {
-        cfg_if::cfg_if! {
-            if #[cfg(feature = "llvm")] {
-                let compiler = LLVM::new();
-                let store = Store::new(compiler);
-            } else {
-                let store = Store::default();
-            }
-        }
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self, WitnessError> {
+        let store = new_store();
         let module = Module::from_file(&store, path)?;
         Self::from_module(module, store)
     }
 
-    pub fn from_module(module: Module, mut store: Store) -> Result<Self> {
+    /// Same as [`Self::from_file`], but the compiled module is cached in `cache_dir` and reused by
+    /// later calls instead of compiling the wasm again. Not available on wasm32, where modules
+    /// cannot be serialized.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_file_cached(
+        path: impl AsRef<std::path::Path>,
+        cache_dir: impl AsRef<std::path::Path>,
+    ) -> Result<Self, WitnessError> {
+        let store = new_store();
+        let module = cache::load_or_compile(&store, path, cache_dir)?;
+        Self::from_module(module, store)
+    }
+
+    /// Creates a new, independent instance of the same witness generator, without compiling it
+    /// again. The log sink is not carried over.
+    pub fn try_clone(&self) -> Result<Self, WitnessError> {
+        let store = Store::new(self.store.engine().clone());
+        Self::from_module(self.module.clone(), store)
+    }
+
+    pub fn from_module(module: Module, mut store: Store) -> Result<Self, WitnessError> {
         // Set up the memory
-        let memory = Memory::new(&mut store, MemoryType::new(2000, None, false)).unwrap();
+        let memory = Memory::new(&mut store, MemoryType::new(2000, None, false))?;
+        let env = FunctionEnv::new(
+            &mut store,
+            runtime::RuntimeEnv {
+                memory: Some(memory.clone()),
+                ..Default::default()
+            },
+        );
         let import_object = imports! {
             "env" => {
                 "memory" => memory.clone(),
             },
             // Host function callbacks from the WASM
             "runtime" => {
-                "error" => runtime::error(&mut store),
+                "error" => runtime::error(&mut store, &env),
                 "logSetSignal" => runtime::log_signal(&mut store),
                 "logGetSignal" => runtime::log_signal(&mut store),
                 "logFinishComponent" => runtime::log_component(&mut store),
                 "logStartComponent" => runtime::log_component(&mut store),
                 "log" => runtime::log_component(&mut store),
-                "exceptionHandler" => runtime::exception_handler(&mut store),
-                "showSharedRWMemory" => runtime::show_memory(&mut store),
-                "printErrorMessage" => runtime::print_error_message(&mut store),
-                "writeBufferMessage" => runtime::write_buffer_message(&mut store),
+                "exceptionHandler" => runtime::exception_handler(&mut store, &env),
+                "showSharedRWMemory" => runtime::show_memory(&mut store, &env),
+                "printErrorMessage" => runtime::print_error_message(&mut store, &env),
+                "writeBufferMessage" => runtime::write_buffer_message(&mut store, &env),
             }
         };
-        let instance = Wasm::new(Instance::new(&mut store, &module, &import_object)?);
+        let instance = Instance::new(&mut store, &module, &import_object)?;
+        env.as_mut(&mut store).instance = Some(instance.clone());
+        let instance = Wasm::new(instance);
 
         let version = instance.get_version(&mut store).unwrap_or(1);
 
@@ -143,19 +149,19 @@ impl WitnessCalculator {
             instance: Wasm,
             memory: Memory,
             version: u32,
-        ) -> Result<WitnessCalculator> {
+            env: FunctionEnv<runtime::RuntimeEnv>,
+            module: Module,
+        ) -> Result<WitnessCalculator, WitnessError> {
+            let io = Circom2Io::new(&instance, &mut store)?;
             let n32 = instance.get_field_num_len32(&mut store)?;
-            let mut safe_memory = SafeMemory::new(memory, n32 as usize, U256::ZERO);
             instance.get_raw_prime(&mut store)?;
-            let mut arr = vec![0; n32 as usize];
+            let mut prime = vec![0; n32 as usize];
             for i in 0..n32 {
-                let res = instance.read_shared_rw_memory(&mut store, i)?;
-                arr[i as usize] = res;
+                prime[i as usize] = instance.read_shared_rw_memory(&mut store, i)?;
             }
-            let prime = u256_from_vec_u32(&arr);
 
-            let n64 = ((prime.bits() - 1) / 64 + 1) as u32;
-            safe_memory.prime = prime;
+            let n64 = (bits(&prime).saturating_sub(1) / 64 + 1) as u32;
+            let safe_memory = SafeMemory::new(memory, n32 as usize, prime);
 
             Ok(WitnessCalculator {
                 instance,
@@ -163,6 +169,9 @@ impl WitnessCalculator {
                 memory: safe_memory,
                 n64,
                 circom_version: version,
+                env,
+                module,
+                io: Some(io),
             })
         }
 
@@ -171,15 +180,22 @@ impl WitnessCalculator {
             instance: Wasm,
             memory: Memory,
             version: u32,
-        ) -> Result<WitnessCalculator> {
+            env: FunctionEnv<runtime::RuntimeEnv>,
+            module: Module,
+        ) -> Result<WitnessCalculator, WitnessError> {
             // Fallback to Circom 1 behavior
-            let n32 = (instance.get_fr_len(&mut store)? >> 2) - 2;
-            let mut safe_memory = SafeMemory::new(memory, n32 as usize, U256::ZERO);
+            // a field element is the short value, the type word and at least one limb
+            let n32 = (instance.get_fr_len(&mut store)? >> 2)
+                .checked_sub(2)
+                .filter(|&n32| n32 > 0)
+                .ok_or_else(|| WitnessError::InvalidReturnType("getFrLen".to_string()))?;
+            let mut safe_memory = SafeMemory::new(memory, n32 as usize, vec![]);
             let ptr = instance.get_ptr_raw_prime(&mut store)?;
-            let prime = safe_memory.read_big(&store, ptr as usize);
+            let prime = safe_memory.read_big(&store, ptr as usize)?;
 
-            let n64 = ((prime.bits() - 1) / 64 + 1) as u32;
+            let n64 = (bits(&prime).saturating_sub(1) / 64 + 1) as u32;
             safe_memory.prime = prime;
+            env.as_mut(&mut store).safe_memory = Some(safe_memory.clone());
 
             Ok(WitnessCalculator {
                 instance,
@@ -187,6 +203,10 @@ impl WitnessCalculator {
                 memory: safe_memory,
                 n64,
                 circom_version: version,
+                env,
+                module,
+                #[cfg(feature = "circom-2")]
+                io: None,
             })
         }
 
@@ -200,21 +220,63 @@ impl WitnessCalculator {
         cfg_if::cfg_if! {
             if #[cfg(feature = "circom-2")] {
                 match version {
-                    2 => new_circom2(store, instance, memory, version),
-                    1 => new_circom1(store, instance, memory, version),
-                    _ => panic!("Unknown Circom version")
+                    2 => new_circom2(store, instance, memory, version, env, module),
+                    1 => new_circom1(store, instance, memory, version, env, module),
+                    _ => Err(WitnessError::UnsupportedVersion(version)),
                 }
             } else {
-                new_circom1(store, instance, memory, version)
+                new_circom1(store, instance, memory, version, env, module)
             }
         }
     }
 
+    /// Checks that the witness generator was compiled for the field `F`, i.e. that its prime is
+    /// `F::MODULUS`.
+    pub fn check_prime<F: PrimeField>(&self) -> Result<(), WitnessError> {
+        let mut found = self
+            .memory
+            .prime
+            .iter()
+            .flat_map(|limb| limb.to_le_bytes())
+            .collect::<Vec<_>>();
+        let len = found.len().max(field_size::<F>());
+        let expected = modulus_le_bytes::<F>(len);
+        found.resize(len, 0);
+        if expected != found {
+            return Err(WitnessError::PrimeMismatch {
+                expected: F::MODULUS.to_string(),
+                found: le_bytes_to_hex(&found),
+            });
+        }
+        Ok(())
+    }
+
+    /// Number of values of the witnesses computed by the witness generator.
+    pub fn witness_size(&mut self) -> Result<usize, WitnessError> {
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "circom-2")] {
+                let size = match self.circom_version {
+                    2 => self.instance.get_witness_size(&mut self.store)?,
+                    _ => self.instance.get_n_vars(&mut self.store)?,
+                };
+            } else {
+                let size = self.instance.get_n_vars(&mut self.store)?;
+            }
+        }
+        Ok(size as usize)
+    }
+
     pub fn calculate_witness<F: PrimeField>(
         &mut self,
         input: Vec<(String, Vec<F>)>,
         sanity_check: bool,
-    ) -> Result<Vec<F>> {
+    ) -> Result<Vec<F>, WitnessError> {
+        self.check_prime::<F>()?;
+
+        // drop the messages left over by a previous run
+        let env = self.env.as_mut(&mut self.store);
+        env.error_message.clear();
+        env.log_message.clear();
         self.instance.init(&mut self.store, sanity_check)?;
 
         cfg_if::cfg_if! {
@@ -222,7 +284,7 @@ impl WitnessCalculator {
                 match self.circom_version {
                     2 => self.calculate_witness_circom2(input, sanity_check),
                     1 => self.calculate_witness_circom1(input, sanity_check),
-                    _ => panic!("Unknown Circom version")
+                    _ => Err(WitnessError::UnsupportedVersion(self.circom_version)),
                 }
             } else {
                 self.calculate_witness_circom1(input, sanity_check)
@@ -235,12 +297,12 @@ impl WitnessCalculator {
         &mut self,
         input: Vec<(String, Vec<F>)>,
         sanity_check: bool,
-    ) -> Result<Vec<F>> {
+    ) -> Result<Vec<F>, WitnessError> {
         self.instance.init(&mut self.store, sanity_check)?;
 
-        let old_mem_free_pos = self.memory.free_pos(&self.store);
-        let p_sig_offset = self.memory.alloc_u32(&self.store);
-        let p_fr = self.memory.alloc_fr(&self.store);
+        let old_mem_free_pos = self.memory.free_pos(&self.store)?;
+        let p_sig_offset = self.memory.alloc_u32(&self.store)?;
+        let p_fr = self.memory.alloc_fr(&self.store)?;
 
         // allocate the inputs
         for (name, values) in input {
@@ -249,11 +311,10 @@ impl WitnessCalculator {
             self.instance
                 .get_signal_offset32(&mut self.store, p_sig_offset, 0, msb, lsb)?;
 
-            let sig_offset = self.memory.read_u32(&self.store, p_sig_offset as usize) as usize;
+            let sig_offset = self.memory.read_u32(&self.store, p_sig_offset as usize)? as usize;
 
-            for (i, _value) in values.into_iter().enumerate() {
-                self.memory
-                    .write_fr(&self.store, p_fr as usize, U256::ZERO)?; // TODO: FIXME
+            for (i, value) in values.into_iter().enumerate() {
+                self.memory.write_fr(&self.store, p_fr as usize, value)?;
                 self.instance
                     .set_signal(&mut self.store, 0, 0, (sig_offset + i) as u32, p_fr)?;
             }
@@ -264,11 +325,11 @@ impl WitnessCalculator {
         let n_vars = self.instance.get_n_vars(&mut self.store)?;
         for i in 0..n_vars {
             let ptr = self.instance.get_ptr_witness(&mut self.store, i)? as usize;
-            let el = self.memory.read_fr(&self.store, ptr);
+            let el = self.memory.read_fr(&self.store, ptr)?;
             w.push(el);
         }
 
-        self.memory.set_free_pos(&self.store, old_mem_free_pos);
+        self.memory.set_free_pos(&self.store, old_mem_free_pos)?;
 
         Ok(w)
     }
@@ -279,49 +340,103 @@ impl WitnessCalculator {
         &mut self,
         input: Vec<(String, Vec<F>)>,
         sanity_check: bool,
-    ) -> Result<Vec<F>> {
+    ) -> Result<Vec<F>, WitnessError> {
         self.instance.init(&mut self.store, sanity_check)?;
 
-        let n32 = self.instance.get_field_num_len32(&mut self.store)?;
+        let io = self
+            .io
+            .as_ref()
+            .ok_or(WitnessError::UnsupportedVersion(self.circom_version))?;
 
-        // allocate the inputs
+        // check the inputs against the signals of the main component before writing any of them
+        let mut signals = Vec::with_capacity(input.len());
+        let mut input_counter = 0;
         for (name, values) in input {
             let (msb, lsb) = fnv(&name);
+            let size = self
+                .instance
+                .get_input_signal_size(&mut self.store, msb, lsb)?
+                .ok_or_else(|| WitnessError::UnknownSignal(name.clone()))?;
+            if values.len() != size {
+                return Err(WitnessError::WrongInputLength {
+                    name,
+                    expected: size,
+                    found: values.len(),
+                });
+            }
+            input_counter += size;
+            signals.push((msb, lsb, values));
+        }
+        let input_size = self.instance.get_input_size(&mut self.store)? as usize;
+        if input_counter < input_size {
+            return Err(WitnessError::MissingInputs {
+                found: input_counter,
+                expected: input_size,
+            });
+        }
 
+        // allocate the inputs, on the n32 limbs used by the witness generator whatever the size
+        // of the repr of F
+        for (msb, lsb, values) in signals {
             for (i, value) in values.into_iter().enumerate() {
-                let f_arr = to_vec_u32(value);
-                for j in 0..n32 {
-                    self.instance
-                        .write_shared_rw_memory(&mut self.store, j, f_arr[j as usize])?;
-                }
-                self.instance
-                    .set_input_signal(&mut self.store, msb, lsb, i as u32)?;
+                let mut limbs = value.to_le_limbs();
+                limbs.resize(io.n32(), 0);
+                io.write_shared(&mut self.store, &limbs)?;
+                io.set_input_signal(&mut self.store, msb, lsb, i as u32)?;
             }
         }
 
-        let mut w = Vec::new();
-
         let witness_size = self.instance.get_witness_size(&mut self.store)?;
+        let mut w = Vec::with_capacity(witness_size as usize);
+        let mut arr = vec![0; io.n32()];
         for i in 0..witness_size {
-            self.instance.get_witness(&mut self.store, i)?;
-            let mut arr = vec![0; n32 as usize];
-            for j in 0..n32 {
-                arr[(n32 as usize) - 1 - (j as usize)] =
-                    self.instance.read_shared_rw_memory(&mut self.store, j)?;
-            }
-            w.push(from_vec_u32(arr));
+            io.get_witness(&mut self.store, i)?;
+            io.read_shared(&mut self.store, &mut arr)?;
+            w.push(from_le_limbs_reduced(&arr));
         }
 
         Ok(w)
     }
 
-    pub fn get_witness_buffer(&self, store: &mut impl AsStoreMut) -> Result<Vec<u8>> {
-        let ptr = self.instance.get_ptr_witness_buffer(store)? as usize;
-        let len = self.instance.get_n_vars(store)? * self.n64 * 8;
-        let view = self.memory.view(store);
-        let bytes = unsafe { view.data_unchecked() };
+    /// Number of field elements of an input signal of the main component, `None` if the circuit
+    /// has no such input. Requires a Circom 2.1 witness generator.
+    #[cfg(feature = "circom-2")]
+    pub fn input_signal_size(&mut self, name: &str) -> Result<Option<usize>, WitnessError> {
+        let (msb, lsb) = fnv(name);
+        self.instance
+            .get_input_signal_size(&mut self.store, msb, lsb)
+    }
+
+    /// Total number of field elements of the inputs of the main component. Requires a Circom 2.1
+    /// witness generator.
+    #[cfg(feature = "circom-2")]
+    pub fn input_size(&mut self) -> Result<usize, WitnessError> {
+        Ok(self.instance.get_input_size(&mut self.store)? as usize)
+    }
 
-        let arr = bytes[ptr..ptr + len as usize].to_vec();
+    /// Sends the lines printed by the circuit with `log()` to `callback` instead of stderr.
+    pub fn set_log_callback(&mut self, callback: impl FnMut(&str) + Send + 'static) {
+        self.env.as_mut(&mut self.store).log_sink = Some(runtime::LogSink(Box::new(callback)));
+    }
+
+    /// Writes the lines printed by the circuit with `log()` to `writer` instead of stderr.
+    pub fn set_log_writer(&mut self, mut writer: impl Write + Send + 'static) {
+        self.set_log_callback(move |line| {
+            // logging is best effort and must not abort the witness calculation
+            let _ = writeln!(writer, "{line}");
+        });
+    }
+
+    pub fn get_witness_buffer(&self, store: &mut impl AsStoreMut) -> Result<Vec<u8>, WitnessError> {
+        let ptr = self.instance.get_ptr_witness_buffer(store)?;
+        let len = self
+            .instance
+            .get_n_vars(store)?
+            .checked_mul(self.n64 * 8)
+            .ok_or(MemoryAccessError::Overflow)?;
+
+        let mut arr = vec![0; len as usize];
+        self.memory.view(store).read(u64::from(ptr), &mut arr)?;
 
         Ok(arr)
     }
@@ -329,46 +444,227 @@ impl WitnessCalculator {
 
 // callback hooks for debugging
 mod runtime {
-    use super::{AsStoreMut, ExitCode, Function, Result, RuntimeError};
+    use super::{
+        AsStoreMut, Function, FunctionEnv, FunctionEnvMut, Instance, Memory, RuntimeError,
+    };
+    use crate::witness::{SafeMemory, WitnessError};
+    use crate::writer::limbs_to_decimal_string;
+    use std::fmt;
+    use wasmer::Value;
+
+    /// Receives the lines printed by the circuit with `log()`.
+    pub struct LogSink(pub Box<dyn FnMut(&str) + Send>);
+
+    impl fmt::Debug for LogSink {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("LogSink")
+        }
+    }
 
-    pub fn error(store: &mut impl AsStoreMut) -> Function {
-        #[allow(unused)]
+    /// State shared with the host functions, filled in once the module is instantiated.
+    #[derive(Debug, Default)]
+    pub struct RuntimeEnv {
+        pub instance: Option<Instance>,
+        pub memory: Option<Memory>,
+        /// The Circom 1 memory once the prime is known, to decode the field elements of errors.
+        pub safe_memory: Option<SafeMemory>,
+        /// Messages printed through `printErrorMessage` since the last exception.
+        pub error_message: String,
+        /// Line being built by `log()`, emitted once the runtime writes its final newline.
+        pub log_message: String,
+        /// Where to send the `log()` lines, stderr if unset.
+        pub log_sink: Option<LogSink>,
+    }
+
+    impl RuntimeEnv {
+        fn export(&self, name: &str) -> Option<Function> {
+            self.instance
+                .as_ref()
+                .and_then(|instance| instance.exports.get_function(name).ok())
+                .cloned()
+        }
+
+        fn push_log(&mut self, message: &str) {
+            // items of the same `log()` call are separated by a space
+            if !self.log_message.is_empty() {
+                self.log_message.push(' ');
+            }
+            self.log_message.push_str(message);
+        }
+
+        fn flush_log(&mut self) {
+            let line = std::mem::take(&mut self.log_message);
+            match self.log_sink.as_mut() {
+                Some(LogSink(sink)) => sink(&line),
+                None => eprintln!("{line}"),
+            }
+        }
+    }
+
+    fn call_u32(
+        store: &mut impl AsStoreMut,
+        func: &Function,
+        params: &[Value],
+    ) -> Result<u32, RuntimeError> {
+        match func.call(store, params)?.as_ref() {
+            [Value::I32(v)] => Ok(*v as u32),
+            _ => Err(RuntimeError::new(
+                "unexpected return value from the runtime",
+            )),
+        }
+    }
+
+    /// Reads the message the Circom 2 runtime exposes one character at a time through
+    /// `getMessageChar`.
+    fn read_message(env: &mut FunctionEnvMut<'_, RuntimeEnv>) -> Result<String, RuntimeError> {
+        let (data, mut store) = env.data_and_store_mut();
+        let Some(get_message_char) = data.export("getMessageChar") else {
+            return Ok(String::new());
+        };
+
+        let mut message = String::new();
+        loop {
+            match call_u32(&mut store, &get_message_char, &[])? {
+                0 => break,
+                c => message.push(char::from(c as u8)),
+            }
+        }
+        Ok(message)
+    }
+
+    /// Reads the nul-terminated string at `ptr` in the Circom 1 memory.
+    fn p2str(env: &FunctionEnvMut<'_, RuntimeEnv>, ptr: i32) -> String {
+        let Some(memory) = env.data().memory.as_ref() else {
+            return String::new();
+        };
+        let view = memory.view(env);
+        let mut bytes = Vec::new();
+        let mut offset = ptr as u32 as u64;
+        while let Ok(b) = view.read_u8(offset) {
+            if b == 0 {
+                break;
+            }
+            bytes.push(b);
+            offset += 1;
+        }
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+
+    // Circom 1.0
+    pub fn error(store: &mut impl AsStoreMut, env: &FunctionEnv<RuntimeEnv>) -> Function {
         #[allow(clippy::many_single_char_names)]
-        fn func(a: i32, b: i32, c: i32, d: i32, e: i32, f: i32) -> Result<(), RuntimeError> {
-            // NOTE: We can also get more information why it is failing, see p2str etc here:
+        fn func(
+            env: FunctionEnvMut<'_, RuntimeEnv>,
+            code: i32,
+            pstr: i32,
+            a: i32,
+            b: i32,
+            c: i32,
+            d: i32,
+        ) -> Result<(), RuntimeError> {
+            // Same messages as
             // https://github.com/iden3/circom_runtime/blob/master/js/witness_calculator.js#L52-L64
-            println!("runtime error, exiting early: {a} {b} {c} {d} {e} {f}",);
-            Err(RuntimeError::user(Box::new(ExitCode(1))))
+            let fr = |ptr: i32| match env.data().safe_memory.as_ref() {
+                Some(memory) => memory.fr_to_string(&env, ptr as u32 as usize),
+                None => Ok(ptr.to_string()),
+            };
+            let message = || -> Result<WitnessError, WitnessError> {
+                Ok(match code {
+                    // assert mismatch of the values at `b` and `c`, `d` points to the label of
+                    // the failing signal
+                    7 => WitnessError::AssertionFailure(format!(
+                        "{} {} != {} {}",
+                        p2str(&env, pstr),
+                        fr(b)?,
+                        fr(c)?,
+                        p2str(&env, d)
+                    )),
+                    9 => WitnessError::Exception {
+                        code: 9,
+                        message: format!("{} {} {}", p2str(&env, pstr), fr(b)?, p2str(&env, c)),
+                    },
+                    _ => WitnessError::Exception {
+                        code: code as u32,
+                        message: format!("{} {a} {b} {c} {d}", p2str(&env, pstr)),
+                    },
+                })
+            };
+            // a bad pointer in the arguments is reported instead of the error itself
+            let err = message().unwrap_or_else(|err| err);
+            Err(RuntimeError::user(Box::new(err)))
         }
-        Function::new_typed(store, func)
+        Function::new_typed_with_env(store, env, func)
     }
 
     // Circom 2.0
-    pub fn exception_handler(store: &mut impl AsStoreMut) -> Function {
-        #[allow(unused)]
-        fn func(a: i32) {}
-        Function::new_typed(store, func)
+    pub fn exception_handler(
+        store: &mut impl AsStoreMut,
+        env: &FunctionEnv<RuntimeEnv>,
+    ) -> Function {
+        fn func(mut env: FunctionEnvMut<'_, RuntimeEnv>, code: i32) -> Result<(), RuntimeError> {
+            let trace = std::mem::take(&mut env.data_mut().error_message);
+            Err(RuntimeError::user(Box::new(WitnessError::from_exception(
+                code as u32,
+                &trace,
+            ))))
+        }
+        Function::new_typed_with_env(store, env, func)
     }
 
     // Circom 2.0
-    pub fn show_memory(store: &mut impl AsStoreMut) -> Function {
-        #[allow(unused)]
-        fn func() {}
-        Function::new_typed(store, func)
+    pub fn show_memory(store: &mut impl AsStoreMut, env: &FunctionEnv<RuntimeEnv>) -> Function {
+        // logs the field element held in the shared memory
+        fn func(mut env: FunctionEnvMut<'_, RuntimeEnv>) -> Result<(), RuntimeError> {
+            let (data, mut store) = env.data_and_store_mut();
+            let (Some(get_len), Some(read)) = (
+                data.export("getFieldNumLen32"),
+                data.export("readSharedRWMemory"),
+            ) else {
+                return Ok(());
+            };
+
+            let n32 = call_u32(&mut store, &get_len, &[])?;
+            let limbs = (0..n32)
+                .map(|j| call_u32(&mut store, &read, &[j.into()]))
+                .collect::<Result<Vec<_>, _>>()?;
+            data.push_log(&limbs_to_decimal_string(limbs));
+            Ok(())
+        }
+        Function::new_typed_with_env(store, env, func)
     }
 
     // Circom 2.0
-    pub fn print_error_message(store: &mut impl AsStoreMut) -> Function {
-        #[allow(unused)]
-        fn func() {}
-        Function::new_typed(store, func)
+    pub fn print_error_message(
+        store: &mut impl AsStoreMut,
+        env: &FunctionEnv<RuntimeEnv>,
+    ) -> Function {
+        fn func(mut env: FunctionEnvMut<'_, RuntimeEnv>) -> Result<(), RuntimeError> {
+            let message = read_message(&mut env)?;
+            let error_message = &mut env.data_mut().error_message;
+            error_message.push_str(&message);
+            error_message.push('\n');
+            Ok(())
+        }
+        Function::new_typed_with_env(store, env, func)
     }
 
     // Circom 2.0
-    pub fn write_buffer_message(store: &mut impl AsStoreMut) -> Function {
-        #[allow(unused)]
-        fn func() {}
-        Function::new_typed(store, func)
+    pub fn write_buffer_message(
+        store: &mut impl AsStoreMut,
+        env: &FunctionEnv<RuntimeEnv>,
+    ) -> Function {
+        fn func(mut env: FunctionEnvMut<'_, RuntimeEnv>) -> Result<(), RuntimeError> {
+            let message = read_message(&mut env)?;
+            let data = env.data_mut();
+            // every `log()` call ends with a lone newline
+            if message == "\n" {
+                data.flush_log();
+            } else {
+                data.push_log(&message);
+            }
+            Ok(())
+        }
+        Function::new_typed_with_env(store, env, func)
     }
 
     pub fn log_signal(store: &mut impl AsStoreMut) -> Function {
@@ -0,0 +1,281 @@
+// Copyright (c) Lurk Lab
+// SPDX-License-Identifier: MIT
+
+use std::fs::File;
+use std::io::{self, BufWriter, Error, ErrorKind, Write};
+use std::marker::PhantomData;
+use std::path::Path;
+
+use byteorder::{LittleEndian, WriteBytesExt};
+use ff::PrimeField;
+
+use crate::field::CircomField;
+use crate::r1cs::R1CS;
+
+const HEADER_TYPE: u32 = 1;
+const CONSTRAINT_TYPE: u32 = 2;
+const WIRE2LABEL_TYPE: u32 = 3;
+
+/// Byte size of the encoding of a field element in Circom's files, the modulus rounded up to
+/// 64-bit words. It can differ from the size of `F::Repr`, e.g. 8 bytes for Goldilocks.
+pub(crate) fn field_size<F: PrimeField>() -> usize {
+    (F::NUM_BITS as usize).saturating_sub(1) / 64 * 8 + 8
+}
+
+/// Little endian encoding of `F::MODULUS` on `len` bytes.
+pub(crate) fn modulus_le_bytes<F: PrimeField>(len: usize) -> Vec<u8> {
+    let hex = F::MODULUS.trim_start_matches("0x").as_bytes();
+    let mut bytes = vec![0u8; len];
+    for (byte, chunk) in bytes.iter_mut().zip(hex.rchunks(2)) {
+        let chunk = std::str::from_utf8(chunk).expect("MODULUS is ascii");
+        *byte = u8::from_str_radix(chunk, 16).expect("MODULUS is a hex string");
+    }
+    bytes
+}
+
+/// Hexadecimal representation, `0x` prefixed, of a little endian integer.
+pub(crate) fn le_bytes_to_hex(bytes: &[u8]) -> String {
+    let hex: String = bytes.iter().rev().map(|b| format!("{b:02x}")).collect();
+    format!("0x{hex}")
+}
+
+/// Decimal representation of a field element, as used in Circom's json files.
+pub(crate) fn to_decimal_string<F: PrimeField>(f: &F) -> String {
+    limbs_to_decimal_string(f.to_le_limbs())
+}
+
+/// Decimal representation of a little endian base 2^32 number.
+pub(crate) fn limbs_to_decimal_string(mut limbs: Vec<u32>) -> String {
+    // repeatedly divided by 10^9
+    let mut digits = Vec::new();
+    while limbs.iter().any(|&l| l != 0) {
+        let mut rem = 0u64;
+        for limb in limbs.iter_mut().rev() {
+            let cur = (rem << 32) | u64::from(*limb);
+            *limb = (cur / 1_000_000_000) as u32;
+            rem = cur % 1_000_000_000;
+        }
+        digits.push(rem as u32);
+    }
+    match digits.split_last() {
+        None => "0".to_string(),
+        Some((last, rest)) => {
+            let mut res = last.to_string();
+            for d in rest.iter().rev() {
+                res.push_str(&format!("{d:09}"));
+            }
+            res
+        }
+    }
+}
+
+/// Writes the little endian encoding of a field element on `size` bytes, which hold every
+/// element of `F`.
+fn write_field<W: Write, F: PrimeField>(mut writer: W, f: &F, size: usize) -> io::Result<()> {
+    let mut bytes = f.to_le_bytes();
+    bytes.resize(size, 0);
+    writer.write_all(&bytes)
+}
+
+/// `value` as a `u32` field of the file format, an `InvalidInput` error if it does not fit.
+fn to_u32(value: usize, what: &str) -> io::Result<u32> {
+    u32::try_from(value).map_err(|_| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("{what} {value} does not fit in 32 bits"),
+        )
+    })
+}
+
+fn write_constraint_vec<W: Write, F: PrimeField>(
+    mut writer: W,
+    lc: &[(usize, F)],
+    field_size: usize,
+) -> io::Result<()> {
+    writer.write_u32::<LittleEndian>(to_u32(lc.len(), "number of terms")?)?;
+    for (index, coeff) in lc {
+        writer.write_u32::<LittleEndian>(to_u32(*index, "wire")?)?;
+        write_field(&mut writer, coeff, field_size)?;
+    }
+    Ok(())
+}
+
+/// write r1cs in the iden3 binary format to a writer
+///
+/// Sections are emitted in the same order as the Circom compiler does (constraints, header and
+/// wire2label), so that an R1CS read from a Circom generated file is written back byte-for-byte.
+/// Field elements are encoded on `r1cs.field_size` bytes, which must hold every element of `F`,
+/// and `r1cs.prime` must be the modulus of `F` on that many bytes.
+pub fn write_r1cs<F: PrimeField, W: Write>(r1cs: &R1CS<F>, mut writer: W) -> io::Result<()> {
+    let field_size = r1cs.field_size;
+    if field_size.saturating_mul(8) < F::NUM_BITS as usize {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("field elements of {field_size} bytes cannot hold the elements of the field"),
+        ));
+    }
+    let prime = modulus_le_bytes::<F>(field_size);
+    if r1cs.prime != prime {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "the prime of the r1cs is not the modulus of the field",
+        ));
+    }
+
+    writer.write_all(&[0x72, 0x31, 0x63, 0x73])?; // magic = "r1cs"
+    writer.write_u32::<LittleEndian>(1)?; // version
+    writer.write_u32::<LittleEndian>(3)?; // number of sections
+
+    let lc_size = |lc: &[(usize, F)]| 4 + lc.len() * (4 + field_size);
+    let constraints_size: usize = r1cs
+        .constraints
+        .iter()
+        .map(|(a, b, c)| lc_size(a) + lc_size(b) + lc_size(c))
+        .sum();
+    writer.write_u32::<LittleEndian>(CONSTRAINT_TYPE)?;
+    writer.write_u64::<LittleEndian>(constraints_size as u64)?;
+    for (a, b, c) in &r1cs.constraints {
+        write_constraint_vec(&mut writer, a, field_size)?;
+        write_constraint_vec(&mut writer, b, field_size)?;
+        write_constraint_vec(&mut writer, c, field_size)?;
+    }
+
+    writer.write_u32::<LittleEndian>(HEADER_TYPE)?;
+    writer.write_u64::<LittleEndian>(32 + field_size as u64)?;
+    writer.write_u32::<LittleEndian>(to_u32(field_size, "field size")?)?;
+    writer.write_all(&prime)?;
+    writer.write_u32::<LittleEndian>(to_u32(r1cs.num_variables, "number of wires")?)?;
+    writer.write_u32::<LittleEndian>(to_u32(r1cs.num_pub_out, "number of public outputs")?)?;
+    writer.write_u32::<LittleEndian>(to_u32(r1cs.num_pub_in, "number of public inputs")?)?;
+    writer.write_u32::<LittleEndian>(to_u32(r1cs.num_prv_in, "number of private inputs")?)?;
+    writer.write_u64::<LittleEndian>(r1cs.num_labels as u64)?;
+    writer.write_u32::<LittleEndian>(to_u32(r1cs.constraints.len(), "number of constraints")?)?;
+
+    writer.write_u32::<LittleEndian>(WIRE2LABEL_TYPE)?;
+    writer.write_u64::<LittleEndian>(r1cs.wire_mapping.len() as u64 * 8)?;
+    for label in &r1cs.wire_mapping {
+        writer.write_u64::<LittleEndian>(*label as u64)?;
+    }
+
+    writer.flush()
+}
+
+/// write r1cs in the iden3 binary format to a file
+pub fn write_r1cs_to_file<F: PrimeField>(
+    r1cs: &R1CS<F>,
+    filename: impl AsRef<Path>,
+) -> io::Result<()> {
+    write_r1cs(r1cs, BufWriter::new(File::create(filename)?))
+}
+
+/// Streaming writer for the iden3 `.wtns` binary format.
+///
+/// The header is written on creation, then each witness element is appended with
+/// [`WitnessBinWriter::write`], so that the witness never needs to be held in memory as a whole.
+pub struct WitnessBinWriter<F: PrimeField, W: Write> {
+    writer: W,
+    remaining: usize,
+    _field: PhantomData<F>,
+}
+
+impl<F: PrimeField, W: Write> WitnessBinWriter<F, W> {
+    /// Writes the header of a witness of `len` elements.
+    pub fn new(mut writer: W, len: usize) -> io::Result<Self> {
+        let field_size = field_size::<F>();
+
+        writer.write_all(b"wtns")?;
+        writer.write_u32::<LittleEndian>(2)?; // version
+        writer.write_u32::<LittleEndian>(2)?; // number of sections
+
+        writer.write_u32::<LittleEndian>(1)?;
+        writer.write_u64::<LittleEndian>(4 + field_size as u64 + 4)?;
+        writer.write_u32::<LittleEndian>(field_size as u32)?;
+        writer.write_all(&modulus_le_bytes::<F>(field_size))?;
+        writer.write_u32::<LittleEndian>(to_u32(len, "witness length")?)?;
+
+        writer.write_u32::<LittleEndian>(2)?;
+        writer.write_u64::<LittleEndian>((len * field_size) as u64)?;
+
+        Ok(Self {
+            writer,
+            remaining: len,
+            _field: PhantomData,
+        })
+    }
+
+    /// Appends the next witness element.
+    pub fn write(&mut self, f: &F) -> io::Result<()> {
+        if self.remaining == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "more witness elements written than announced in the header",
+            ));
+        }
+        self.remaining -= 1;
+        write_field(&mut self.writer, f, field_size::<F>())
+    }
+
+    /// Checks that the announced number of elements was written, flushes and returns the
+    /// underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.remaining != 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} witness elements missing", self.remaining),
+            ));
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Streaming writer for the snarkjs `witness.json` format, an array of decimal strings.
+pub struct WitnessJsonWriter<F: PrimeField, W: Write> {
+    writer: W,
+    first: bool,
+    _field: PhantomData<F>,
+}
+
+impl<F: PrimeField, W: Write> WitnessJsonWriter<F, W> {
+    /// Opens the json array.
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writer.write_all(b"[")?;
+        Ok(Self {
+            writer,
+            first: true,
+            _field: PhantomData,
+        })
+    }
+
+    /// Appends the next witness element.
+    pub fn write(&mut self, f: &F) -> io::Result<()> {
+        let sep = if self.first { "" } else { "," };
+        self.first = false;
+        write!(self.writer, "{sep}\n \"{}\"", to_decimal_string(f))
+    }
+
+    /// Closes the array, flushes and returns the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.write_all(b"\n]")?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// write witness in the iden3 `.wtns` binary format to a writer
+pub fn write_witness_bin<F: PrimeField, W: Write>(witness: &[F], writer: W) -> io::Result<()> {
+    let mut writer = WitnessBinWriter::new(writer, witness.len())?;
+    for f in witness {
+        writer.write(f)?;
+    }
+    writer.finish().map(|_| ())
+}
+
+/// write witness in the snarkjs `witness.json` format to a writer
+pub fn write_witness_json<F: PrimeField, W: Write>(witness: &[F], writer: W) -> io::Result<()> {
+    let mut writer = WitnessJsonWriter::new(writer)?;
+    for f in witness {
+        writer.write(f)?;
+    }
+    writer.finish().map(|_| ())
+}
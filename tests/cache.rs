@@ -0,0 +1,89 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::fs;
+use std::path::PathBuf;
+
+use circom_scotia::witness::WitnessCalculator;
+use ff::Field;
+use pasta_curves::vesta::Base as Fr;
+
+const COPY_WAT: &str = "tests/fixtures/circom1_copy.wat";
+
+fn cache_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("circom-scotia-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+fn entries(dir: &PathBuf) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect()
+}
+
+fn check_calculator(mut calculator: WitnessCalculator) {
+    let input = vec![Fr::from(3), -Fr::ONE, Fr::from(u64::MAX)];
+    let witness = calculator
+        .calculate_witness(vec![("in".to_string(), input.clone())], true)
+        .unwrap();
+    assert_eq!(witness[4..7], input);
+}
+
+#[test]
+fn compiled_module_is_cached() {
+    let dir = cache_dir("cached");
+
+    check_calculator(WitnessCalculator::from_file_cached(COPY_WAT, &dir).unwrap());
+    let cached = entries(&dir);
+    assert_eq!(cached.len(), 1);
+    let entry = fs::read(&cached[0]).unwrap();
+
+    check_calculator(WitnessCalculator::from_file_cached(COPY_WAT, &dir).unwrap());
+    assert_eq!(entries(&dir), cached);
+    assert_eq!(fs::read(&cached[0]).unwrap(), entry);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn corrupt_cache_entry_is_rebuilt() {
+    let dir = cache_dir("corrupt");
+
+    check_calculator(WitnessCalculator::from_file_cached(COPY_WAT, &dir).unwrap());
+    let cached = entries(&dir);
+    let entry = fs::read(&cached[0]).unwrap();
+
+    let mut corrupt = entry.clone();
+    let last = corrupt.len() - 1;
+    corrupt[last] ^= 0xff;
+    fs::write(&cached[0], corrupt).unwrap();
+    check_calculator(WitnessCalculator::from_file_cached(COPY_WAT, &dir).unwrap());
+    assert_eq!(fs::read(&cached[0]).unwrap(), entry);
+
+    fs::write(&cached[0], b"truncated").unwrap();
+    check_calculator(WitnessCalculator::from_file_cached(COPY_WAT, &dir).unwrap());
+    assert_eq!(fs::read(&cached[0]).unwrap(), entry);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn concurrent_compilations_share_the_cache() {
+    let dir = cache_dir("concurrent");
+
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                check_calculator(WitnessCalculator::from_file_cached(COPY_WAT, &dir).unwrap())
+            });
+        }
+    });
+    // a single entry, and no temporary file left behind
+    let cached = entries(&dir);
+    assert_eq!(cached.len(), 1, "{cached:?}");
+    check_calculator(WitnessCalculator::from_file_cached(COPY_WAT, &dir).unwrap());
+    assert_eq!(entries(&dir), cached);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
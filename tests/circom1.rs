@@ -0,0 +1,150 @@
+mod common;
+
+use circom_scotia::witness::{WitnessCalculator, WitnessError};
+use ff::Field;
+use pasta_curves::vesta::Base as Fr;
+
+const COPY_WAT: &str = include_str!("fixtures/circom1_copy.wat");
+
+fn copy_calculator() -> WitnessCalculator {
+    common::calculator(COPY_WAT)
+}
+
+/// The copy circuit raising the runtime error `code` with the arguments `a, b, c, d` on unknown
+/// signals. The message is at 128, the label `main.out[0]` at 192, the constant one in Montgomery
+/// form at 64, the short -2 at 224 and the long normal p - 3 at 256.
+fn error_calculator(code: i32, args: [i32; 4]) -> WitnessCalculator {
+    let [a, b, c, d] = args;
+    let wat = COPY_WAT
+        .replace(
+            r#"(data (i32.const 128) "Hash not found\00")"#,
+            r#"(data (i32.const 128) "Constraint doesn't match\00")
+  (data (i32.const 192) "main.out[0]\00")
+  (data (i32.const 224) "\fe\ff\ff\ff\00\00\00\00")
+  (data (i32.const 256) "\00\00\00\00\00\00\00\80\fe\ff\ff\ff\20\eb\46\8c\dd\a8\94\09\fc\98\46\22\00\00\00\00\00\00\00\00\00\00\00\00\00\00\00\40")"#,
+        )
+        .replace(
+            "(call $error (i32.const 3) (i32.const 128) (local.get $hash_msb) (local.get $hash_lsb)\n                         (i32.const 0) (i32.const 0))",
+            &format!(
+                "(call $error (i32.const {code}) (i32.const 128) (i32.const {a}) (i32.const {b}) (i32.const {c}) (i32.const {d}))"
+            ),
+        );
+    assert_ne!(wat, COPY_WAT);
+    common::calculator(&wat)
+}
+
+#[test]
+fn circom1_witness_satisfies_r1cs() {
+    let mut calculator = copy_calculator();
+    assert_eq!(calculator.circom_version, 1);
+    let r1cs = common::copy_r1cs::<Fr>(32);
+
+    let short_max = Fr::from(i32::MAX as u64);
+    let inputs = [
+        // short, negative short and long forms
+        [Fr::from(5), -Fr::from(7), Fr::from(u64::MAX)],
+        // bounds of the short form
+        [short_max, short_max + Fr::ONE, -(short_max + Fr::ONE)],
+        [-(short_max + Fr::from(2)), Fr::ZERO, -Fr::ONE],
+    ];
+    for input in inputs {
+        let witness = calculator
+            .calculate_witness(vec![("in".to_string(), input.to_vec())], true)
+            .unwrap();
+
+        assert_eq!(witness[0], Fr::ONE);
+        assert_eq!(witness[1..4], input);
+        assert_eq!(witness[4..7], input);
+        assert!(r1cs.check(&witness).unwrap().is_empty());
+    }
+}
+
+#[test]
+fn circom1_unknown_signal() {
+    let mut calculator = copy_calculator();
+    let err = calculator
+        .calculate_witness(vec![("inn".to_string(), vec![Fr::ONE])], true)
+        .unwrap_err();
+
+    assert!(
+        matches!(&err, WitnessError::Exception { code: 3, message } if message.starts_with("Hash not found")),
+        "{err}"
+    );
+}
+
+/// The raw witness buffer of the witness generator, `n64` words per variable.
+fn witness_buffer(calculator: &mut WitnessCalculator) -> Result<Vec<u8>, WitnessError> {
+    let mut store = std::mem::take(&mut calculator.store);
+    let buffer = calculator.get_witness_buffer(&mut store);
+    calculator.store = store;
+    buffer
+}
+
+#[test]
+fn circom1_witness_buffer() {
+    let mut calculator = copy_calculator();
+    calculator
+        .calculate_witness(vec![("in".to_string(), vec![Fr::from(5); 3])], true)
+        .unwrap();
+    let buffer = witness_buffer(&mut calculator).unwrap();
+    assert_eq!(buffer.len(), 7 * 4 * 8);
+    // the short 1 of the first variable, then its type word
+    assert_eq!(buffer[..8], [0, 0, 0, 0, 0, 0, 0, 0xc0]);
+
+    let wat = COPY_WAT.replace(
+        "(func (export \"getWitnessBuffer\") (result i32) (i32.const 1024))",
+        "(func (export \"getWitnessBuffer\") (result i32) (i32.const -16))",
+    );
+    assert_ne!(wat, COPY_WAT);
+    let err = witness_buffer(&mut common::calculator(&wat)).unwrap_err();
+    assert!(matches!(err, WitnessError::MemoryAccess(_)), "{err}");
+}
+
+#[test]
+fn circom1_error_messages() {
+    let error = |code, args| {
+        error_calculator(code, args)
+            .calculate_witness(vec![("inn".to_string(), vec![Fr::ONE])], true)
+            .unwrap_err()
+            .to_string()
+    };
+
+    // the values are decoded from the short, long normal and long Montgomery forms
+    assert_eq!(
+        error(7, [0, 64, 224, 192]),
+        "Assertion failed in the circuit: Constraint doesn't match 1 != -2 main.out[0]"
+    );
+    assert_eq!(
+        error(7, [0, 256, 64, 192]),
+        "Assertion failed in the circuit: Constraint doesn't match -3 != 1 main.out[0]"
+    );
+    assert_eq!(
+        error(9, [0, 224, 192, 0]),
+        "Circom runtime exception 9: Constraint doesn't match -2 main.out[0]"
+    );
+    assert_eq!(
+        error(5, [1, 2, 3, 4]),
+        "Circom runtime exception 5: Constraint doesn't match 1 2 3 4"
+    );
+}
+
+#[test]
+fn circom1_bad_pointers() {
+    // the witness of the last wire is out of the memory
+    let wat = COPY_WAT.replace(
+        "(i32.add (i32.const 1024) (i32.mul (local.get $w) (i32.const 40))))",
+        "(select (i32.const -16) (i32.add (i32.const 1024) (i32.mul (local.get $w) (i32.const 40)))
+      (i32.eq (local.get $w) (i32.const 6))))",
+    );
+    assert_ne!(wat, COPY_WAT);
+    let err = common::calculator(&wat)
+        .calculate_witness(vec![("in".to_string(), vec![Fr::ONE; 3])], true)
+        .unwrap_err();
+    assert!(matches!(err, WitnessError::MemoryAccess(_)), "{err}");
+
+    // so is a value of an error
+    let err = error_calculator(7, [0, 64, -16, 192])
+        .calculate_witness(vec![("inn".to_string(), vec![Fr::ONE])], true)
+        .unwrap_err();
+    assert!(matches!(err, WitnessError::MemoryAccess(_)), "{err}");
+}
@@ -0,0 +1,201 @@
+//! Inputs of a Circom 2 witness generator, on the copy circuit of `common::circom2_copy_wat`.
+#![cfg(feature = "circom-2")]
+
+mod common;
+
+use circom_scotia::r1cs::CircomConfig;
+use circom_scotia::witness::WitnessError;
+use ff::Field;
+use pasta_curves::vesta::Base as Fr;
+use std::sync::{Arc, Mutex};
+
+fn input(name: &str, len: u64) -> Vec<(String, Vec<Fr>)> {
+    vec![(name.to_string(), (0..len).map(Fr::from).collect())]
+}
+
+#[test]
+fn input_sizes() {
+    let mut calculator = common::circom2_copy_calculator::<Fr>(8);
+    assert_eq!(calculator.input_signal_size("in").unwrap(), Some(3));
+    assert_eq!(calculator.input_signal_size("inn").unwrap(), None);
+    assert_eq!(calculator.input_size().unwrap(), 3);
+
+    let witness = calculator.calculate_witness(input("in", 3), true).unwrap();
+    assert_eq!(witness[0], Fr::ONE);
+    assert_eq!(witness[4..7], [Fr::ZERO, Fr::ONE, Fr::from(2)]);
+}
+
+#[test]
+fn input_errors() {
+    let mut calculator = common::circom2_copy_calculator::<Fr>(8);
+
+    let err = calculator
+        .calculate_witness(input("inn", 3), true)
+        .unwrap_err();
+    assert!(
+        matches!(&err, WitnessError::UnknownSignal(name) if name == "inn"),
+        "{err}"
+    );
+
+    let err = calculator
+        .calculate_witness(input("in", 2), true)
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Input signal in has 3 elements, 2 given");
+
+    let err = calculator
+        .calculate_witness::<Fr>(vec![], true)
+        .unwrap_err();
+    assert!(
+        matches!(
+            err,
+            WitnessError::MissingInputs {
+                found: 0,
+                expected: 3
+            }
+        ),
+        "{err}"
+    );
+
+    // failed runs leave the calculator usable
+    assert!(calculator.calculate_witness(input("in", 3), true).is_ok());
+}
+
+#[test]
+fn circom_2_0_is_rejected() {
+    // Circom 2.0 witness generators describe none of their inputs
+    let wat = common::circom2_copy_wat::<Fr>(8)
+        .replace("(export \"getInputSignalSize\")", "")
+        .replace("(export \"getInputSize\")", "");
+    let mut calculator = common::calculator(&wat);
+
+    let err = calculator
+        .calculate_witness(input("in", 3), true)
+        .unwrap_err();
+    assert!(
+        matches!(&err, WitnessError::RequiresCircom21(name) if name == "getInputSignalSize"),
+        "{err}"
+    );
+    assert!(err.to_string().contains("Circom 2.1 or later"));
+
+    let err = calculator.input_size().unwrap_err();
+    assert!(
+        matches!(&err, WitnessError::RequiresCircom21(name) if name == "getInputSize"),
+        "{err}"
+    );
+}
+
+#[test]
+fn exceptions() {
+    let error = |messages: &[&str], code: i32| {
+        let on_run = format!(
+            "{} (call $exception_handler (i32.const {code}))",
+            "(call $print_error_message)".repeat(messages.len())
+        );
+        let wat = common::circom2_runtime_wat::<Fr>(8, messages, &on_run);
+        common::calculator(&wat)
+            .calculate_witness(input("in", 3), true)
+            .unwrap_err()
+    };
+
+    // the printed messages are the trace of the exception
+    let err = error(
+        &[
+            "Error in template Copy_0 line: 5",
+            "Error in template Main_1 line: 9",
+        ],
+        4,
+    );
+    assert!(
+        matches!(&err, WitnessError::AssertionFailure(message)
+            if message == "Error in template Copy_0 line: 5\nError in template Main_1 line: 9"),
+        "{err}"
+    );
+
+    let err = error(&[], 4);
+    assert_eq!(
+        err.to_string(),
+        "Assertion failed in the circuit: Assert Failed"
+    );
+
+    let err = error(&["Error in template Copy_0 line: 5"], 1);
+    assert_eq!(
+        err.to_string(),
+        "Circom runtime exception 1: Signal not found. Error in template Copy_0 line: 5"
+    );
+
+    let err = error(&[], 42);
+    assert!(
+        matches!(&err, WitnessError::Exception { code: 42, message } if message == "Unknown error"),
+        "{err}"
+    );
+}
+
+#[test]
+fn log_sink() {
+    // log("x =", 42, p) then log("done"), reading p from 1024
+    let show = |value: &str| {
+        format!(
+            "(memory.fill (i32.const 64) (i32.const 0) (i32.const 32)) \
+             (call $copy (i32.const 64) (i32.const {value})) (call $show_shared_rw_memory)"
+        )
+    };
+    let on_run = format!(
+        "(call $write_buffer_message) (i32.store (i32.const 3072) (i32.const 42)) {} {} \
+         (call $write_buffer_message) (call $write_buffer_message) (call $write_buffer_message)",
+        show("3072"),
+        show("1024"),
+    );
+    let wat = common::circom2_runtime_wat::<Fr>(8, &["x =", "\n", "done", "\n"], &on_run);
+    let mut calculator = common::calculator(&wat);
+
+    let lines = Arc::new(Mutex::new(vec![]));
+    let sink = lines.clone();
+    calculator.set_log_callback(move |line| sink.lock().unwrap().push(line.to_string()));
+    calculator.calculate_witness(input("in", 3), true).unwrap();
+
+    // the prime is printed as an integer, as in the Circom runtime
+    let prime = "28948022309329048855892746252171976963363056481941647379679742748393362948097";
+    assert_eq!(
+        *lines.lock().unwrap(),
+        [format!("x = 42 {prime}"), "done".to_string()]
+    );
+}
+
+#[test]
+fn prime_mismatch() {
+    // compiled for the pallas base field, used with the vesta one
+    let mut calculator = common::circom2_copy_calculator::<pasta_curves::Fp>(8);
+    assert!(calculator.check_prime::<pasta_curves::Fp>().is_ok());
+
+    let err = calculator
+        .calculate_witness(input("in", 3), true)
+        .unwrap_err();
+    assert!(matches!(err, WitnessError::PrimeMismatch { .. }), "{err}");
+}
+
+#[test]
+fn witness_size_mismatch() {
+    let dir = std::env::temp_dir().join(format!("circom-scotia-size-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let wasm = dir.join("copy.wat");
+    let r1cs = dir.join("single.r1cs");
+    std::fs::write(&wasm, common::circom2_copy_wat::<Fr>(8)).unwrap();
+    let mut bytes = vec![];
+    common::r1cs::<Fr>(32).to_writer(&mut bytes).unwrap();
+    std::fs::write(&r1cs, bytes).unwrap();
+
+    // the copy circuit has 7 wires, the r1cs 3
+    let err = CircomConfig::<Fr>::new(&wasm, &r1cs).unwrap_err();
+    assert!(
+        matches!(
+            err.downcast_ref::<WitnessError>(),
+            Some(WitnessError::WitnessSizeMismatch {
+                expected: 3,
+                found: 7
+            })
+        ),
+        "{err}"
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
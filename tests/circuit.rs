@@ -0,0 +1,68 @@
+//! The sha256 example synthesized with its public signals as public inputs, see
+//! `synthesize_public` and `CircomCircuit`.
+#![cfg(feature = "circom-2")]
+
+mod common;
+
+use bellpepper_core::test_cs::TestConstraintSystem;
+use bellpepper_core::{Circuit, ConstraintSystem};
+use circom_scotia::r1cs::CircomCircuit;
+use circom_scotia::{calculate_witness, synthesize_public};
+use pasta_curves::vesta::Base as Fr;
+
+fn input() -> Vec<(String, Vec<Fr>)> {
+    vec![("arg_in".to_string(), vec![Fr::from(1), Fr::from(2)])]
+}
+
+#[test]
+fn public_signals() {
+    let cfg = common::sha256_config::<Fr>();
+    let witness = calculate_witness(&cfg, input(), true).unwrap();
+    assert_eq!((cfg.r1cs.num_pub_out, cfg.r1cs.num_pub_in), (1, 2));
+
+    let mut cs = TestConstraintSystem::<Fr>::new();
+    let output = synthesize_public(
+        &mut cs.namespace(|| "sha256"),
+        cfg.r1cs.clone(),
+        Some(witness.clone()),
+    )
+    .unwrap();
+
+    assert!(cs.is_satisfied());
+    assert_eq!(output.get_value(), Some(witness[1]));
+    // the constant one, the output, then the public inputs
+    assert_eq!(cs.num_inputs(), 4);
+    assert!(cs.verify(&[witness[1], Fr::from(1), Fr::from(2)]));
+    assert!(!cs.verify(&[Fr::from(1), Fr::from(2), witness[1]]));
+}
+
+#[test]
+fn circuit_with_witness() {
+    let cfg = common::sha256_config::<Fr>();
+    let circuit = CircomCircuit::with_input(&cfg, input()).unwrap();
+    assert!(std::ptr::eq(circuit.r1cs, &cfg.r1cs));
+    let witness = circuit.witness.clone().unwrap();
+
+    let mut cs = TestConstraintSystem::<Fr>::new();
+    circuit.synthesize(&mut cs.namespace(|| "sha256")).unwrap();
+
+    assert!(cs.is_satisfied());
+    assert_eq!(cs.num_constraints(), cfg.r1cs.constraints.len());
+    // the constant one, then the output and the two public inputs
+    assert_eq!(cs.num_inputs(), cfg.r1cs.num_inputs);
+    assert!(cs.verify(&witness[1..cfg.r1cs.num_inputs]));
+}
+
+#[test]
+fn circuit_without_witness() {
+    let cfg = common::sha256_config::<Fr>();
+    let circuit = CircomCircuit::without_witness(&cfg);
+    assert!(circuit.witness.is_none());
+
+    let mut cs = TestConstraintSystem::<Fr>::new();
+    circuit.synthesize(&mut cs.namespace(|| "sha256")).unwrap();
+
+    // same shape as with a witness
+    assert_eq!(cs.num_constraints(), cfg.r1cs.constraints.len());
+    assert_eq!(cs.num_inputs(), cfg.r1cs.num_inputs);
+}
@@ -0,0 +1,185 @@
+// Helpers shared by the integration tests, not all of them use every helper.
+#![allow(dead_code)]
+
+use circom_scotia::r1cs::{CircomConfig, R1CS};
+use circom_scotia::witness::WitnessCalculator;
+use ff::PrimeField;
+use wasmer::{Module, Store};
+
+/// Witness generator following the Circom 2 runtime ABI for the circuit
+///
+///   template Copy() {
+///       signal input in[3];
+///       signal output out[3];
+///       for (var i = 0; i < 3; i++) out[i] <== in[i];
+///   }
+///
+/// over the field of `F`, with `n32` limbs per field element. Wires are `one, out[0..3],
+/// in[0..3]`, stored at 4096 + wire * n32 * 4. The shared memory is at 64 and the prime at 1024.
+pub fn circom2_copy_wat<F: PrimeField>(n32: usize) -> String {
+    let size = n32 * 4;
+    let prime: String = modulus_le_bytes::<F>(size)
+        .iter()
+        .map(|b| format!("\\{b:02x}"))
+        .collect();
+    format!(
+        r#"(module
+  (memory (export "memory") 1)
+  (data (i32.const 1024) "{prime}")
+  (func $copy (param $dst i32) (param $src i32)
+    (memory.copy (local.get $dst) (local.get $src) (i32.const {size})))
+  (func $wire (param $w i32) (result i32)
+    (i32.add (i32.const 4096) (i32.mul (local.get $w) (i32.const {size}))))
+  (func $shared (param $i i32) (result i32)
+    (i32.add (i32.const 64) (i32.shl (local.get $i) (i32.const 2))))
+
+  (func (export "getVersion") (result i32) (i32.const 2))
+  (func (export "getMinorVersion") (result i32) (i32.const 1))
+  (func (export "getPatchVersion") (result i32) (i32.const 0))
+  (func (export "getFieldNumLen32") (result i32) (i32.const {n32}))
+  (func (export "getSharedRWMemoryStart") (result i32) (i32.const 64))
+  (func (export "getRawPrime") (call $copy (i32.const 64) (i32.const 1024)))
+  (func (export "readSharedRWMemory") (param $i i32) (result i32)
+    (i32.load (call $shared (local.get $i))))
+  (func (export "writeSharedRWMemory") (param $i i32) (param $v i32)
+    (i32.store (call $shared (local.get $i)) (local.get $v)))
+  (func (export "init") (param i32)
+    (memory.fill (call $wire (i32.const 0)) (i32.const 0) (i32.const {size}))
+    (i32.store (call $wire (i32.const 0)) (i32.const 1)))
+
+  ;; only `in` is known
+  (func (export "getInputSignalSize") (param $msb i32) (param $lsb i32) (result i32)
+    (select (i32.const 3) (i32.const 0)
+      (i32.and (i32.eq (local.get $msb) (i32.const 0x08b73807))
+               (i32.eq (local.get $lsb) (i32.const 0xb55c4bbe)))))
+  (func (export "getInputSize") (result i32) (i32.const 3))
+  (func (export "setInputSignal") (param $msb i32) (param $lsb i32) (param $pos i32)
+    (call $copy (call $wire (i32.add (i32.const 4) (local.get $pos))) (i32.const 64))
+    (call $copy (call $wire (i32.add (i32.const 1) (local.get $pos))) (i32.const 64)))
+
+  (func (export "getWitnessSize") (result i32) (i32.const 7))
+  (func (export "getWitness") (param $i i32)
+    (call $copy (i32.const 64) (call $wire (local.get $i)))))"#
+    )
+}
+
+/// [`circom2_copy_wat`] importing the message and exception functions of the Circom 2 runtime.
+/// `messages` are exposed one after the other through `getMessageChar`, and `on_run` is executed
+/// once the last input is set, as Circom runs the main component.
+pub fn circom2_runtime_wat<F: PrimeField>(n32: usize, messages: &[&str], on_run: &str) -> String {
+    let messages: String = messages
+        .iter()
+        .map(|message| format!("{}\\00", message.replace('\n', "\\0a")))
+        .collect();
+    circom2_copy_wat::<F>(n32)
+        .replacen(
+            "(module",
+            &format!(
+                r#"(module
+  (import "runtime" "exceptionHandler" (func $exception_handler (param i32)))
+  (import "runtime" "printErrorMessage" (func $print_error_message))
+  (import "runtime" "writeBufferMessage" (func $write_buffer_message))
+  (import "runtime" "showSharedRWMemory" (func $show_shared_rw_memory))
+  (data (i32.const 2048) "{messages}")
+  (global $message (mut i32) (i32.const 2048))
+  (func (export "getMessageChar") (result i32)
+    (global.set $message (i32.add (global.get $message) (i32.const 1)))
+    (i32.load8_u (i32.sub (global.get $message) (i32.const 1))))"#
+            ),
+            1,
+        )
+        .replace(
+            "(param $pos i32)\n",
+            &format!(
+                "(param $pos i32)\n    (if (i32.eq (local.get $pos) (i32.const 2)) (then {on_run}))\n"
+            ),
+        )
+}
+
+/// The configuration of the sha256 example, without a symbol table.
+pub fn sha256_config<F: PrimeField>() -> CircomConfig<F> {
+    let root = std::path::Path::new("examples/sha256");
+    CircomConfig::new(
+        root.join("circom_sha256.wasm"),
+        root.join("circom_sha256.r1cs"),
+    )
+    .unwrap()
+}
+
+/// Hand-written, partial symbol table of the sha256 example, not the compiler's output: see
+/// `tests/sym.rs`.
+pub const SHA256_PARTIAL_SYM: &str = "tests/fixtures/sha256_partial.sym";
+
+pub fn circom2_copy_calculator<F: PrimeField>(n32: usize) -> WitnessCalculator {
+    calculator(&circom2_copy_wat::<F>(n32))
+}
+
+pub fn calculator(wat: &str) -> WitnessCalculator {
+    let store = Store::default();
+    let module = Module::new(&store, wat).unwrap();
+    WitnessCalculator::from_module(module, store).unwrap()
+}
+
+/// Little endian encoding of `F::MODULUS` on `len` bytes.
+pub fn modulus_le_bytes<F: PrimeField>(len: usize) -> Vec<u8> {
+    let hex = F::MODULUS.trim_start_matches("0x").as_bytes();
+    let mut bytes = vec![0u8; len];
+    for (byte, chunk) in bytes.iter_mut().zip(hex.rchunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(chunk).unwrap(), 16).unwrap();
+    }
+    bytes
+}
+
+/// An element using most of the bits of `F`, `2^(NUM_BITS - 2) + 3`.
+pub fn large<F: PrimeField>() -> F {
+    F::from(2).pow_vartime([u64::from(F::NUM_BITS) - 2]) + F::from(3)
+}
+
+/// An r1cs over `F` with elements encoded on `field_size` bytes and the single constraint
+/// `large * w2 * -1 = 5 * w1`, where `w1` is a public output and `w2` a private input.
+pub fn r1cs<F: PrimeField>(field_size: usize) -> R1CS<F> {
+    R1CS {
+        field_size,
+        prime: modulus_le_bytes::<F>(field_size),
+        num_inputs: 2,
+        num_aux: 1,
+        num_variables: 3,
+        num_pub_out: 1,
+        num_pub_in: 0,
+        num_prv_in: 1,
+        num_labels: 3,
+        constraints: vec![(
+            vec![(2, large())],
+            vec![(0, -F::ONE)],
+            vec![(1, F::from(5))],
+        )],
+        wire_mapping: vec![0, 1, 2],
+    }
+}
+
+/// The r1cs of the copy circuit of [`circom2_copy_wat`] and `fixtures/circom1_copy.wat`:
+/// `out[i] * 1 = in[i]`.
+pub fn copy_r1cs<F: PrimeField>(field_size: usize) -> R1CS<F> {
+    let constraints = (0..3)
+        .map(|i| {
+            (
+                vec![(4 + i, F::ONE)],
+                vec![(0, F::ONE)],
+                vec![(1 + i, F::ONE)],
+            )
+        })
+        .collect();
+    R1CS {
+        field_size,
+        prime: modulus_le_bytes::<F>(field_size),
+        num_inputs: 7,
+        num_aux: 0,
+        num_variables: 7,
+        num_pub_out: 3,
+        num_pub_in: 3,
+        num_prv_in: 0,
+        num_labels: 7,
+        constraints,
+        wire_mapping: (0..7).collect(),
+    }
+}
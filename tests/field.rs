@@ -0,0 +1,170 @@
+//! Conversions between field elements and Circom's little endian integers, on fields with little
+//! and big endian reprs.
+
+mod common;
+
+use circom_scotia::field::CircomField;
+use circom_scotia::r1cs::R1CS;
+#[cfg(feature = "circom-2")]
+use ff::Field;
+use ff::PrimeField;
+
+mod bn254 {
+    /// Scalar field with a little endian repr.
+    pub mod le {
+        use ff::PrimeField;
+
+        #[derive(PrimeField)]
+        #[PrimeFieldModulus = "21888242871839275222246405745257275088548364400416034343698204186575808495617"]
+        #[PrimeFieldGenerator = "5"]
+        #[PrimeFieldReprEndianness = "little"]
+        pub struct Fr([u64; 4]);
+    }
+
+    /// Same field with a big endian repr.
+    pub mod be {
+        use ff::PrimeField;
+
+        #[derive(PrimeField)]
+        #[PrimeFieldModulus = "21888242871839275222246405745257275088548364400416034343698204186575808495617"]
+        #[PrimeFieldGenerator = "5"]
+        #[PrimeFieldReprEndianness = "big"]
+        pub struct Fr([u64; 4]);
+    }
+}
+
+mod bls12_381 {
+    /// Scalar field with a little endian repr.
+    pub mod le {
+        use ff::PrimeField;
+
+        #[derive(PrimeField)]
+        #[PrimeFieldModulus = "52435875175126190479447740508185965837690552500527637822603658699938581184513"]
+        #[PrimeFieldGenerator = "7"]
+        #[PrimeFieldReprEndianness = "little"]
+        pub struct Fr([u64; 4]);
+    }
+
+    /// Same field with a big endian repr.
+    pub mod be {
+        use ff::PrimeField;
+
+        #[derive(PrimeField)]
+        #[PrimeFieldModulus = "52435875175126190479447740508185965837690552500527637822603658699938581184513"]
+        #[PrimeFieldGenerator = "7"]
+        #[PrimeFieldReprEndianness = "big"]
+        pub struct Fr([u64; 4]);
+    }
+}
+
+/// 2^64 * 0x0102030405060708 + 0x090a0b0c
+fn sample<F: PrimeField>() -> F {
+    F::from(0x0102_0304_0506_0708) * F::from(2).pow_vartime([64]) + F::from(0x090a_0b0c)
+}
+
+fn check_conversions<F: PrimeField>() {
+    let f = sample::<F>();
+    let bytes = f.to_le_bytes();
+    assert_eq!(bytes.len(), 32);
+    assert_eq!(bytes[..4], [0x0c, 0x0b, 0x0a, 0x09]);
+    assert_eq!(bytes[8..16], [8, 7, 6, 5, 4, 3, 2, 1]);
+    assert!(bytes[4..8].iter().chain(&bytes[16..]).all(|&b| b == 0));
+
+    let limbs = f.to_le_limbs();
+    assert_eq!(limbs[..4], [0x090a_0b0c, 0, 0x0506_0708, 0x0102_0304]);
+    assert_eq!(F::from_le_limbs(&limbs), Some(f));
+    assert_eq!(F::from_le_bytes(&bytes), Some(f));
+
+    // shorter and longer encodings
+    assert_eq!(F::from_le_bytes(&bytes[..16]), Some(f));
+    assert_eq!(
+        F::from_le_bytes(&[bytes.clone(), vec![0; 16]].concat()),
+        Some(f)
+    );
+    assert_eq!(F::from_le_bytes(&[bytes, vec![1]].concat()), None);
+
+    // -1 is the largest element, the modulus is out of range
+    let minus_one = -F::ONE;
+    let modulus = common::modulus_le_bytes::<F>(32);
+    let mut expected = modulus.clone();
+    expected[0] -= 1;
+    assert_eq!(minus_one.to_le_bytes(), expected);
+    assert_eq!(F::from_le_bytes(&expected), Some(minus_one));
+    assert_eq!(F::from_le_bytes(&modulus), None);
+    assert_eq!(F::from_le_limbs(&[1]), Some(F::ONE));
+}
+
+#[test]
+fn pasta_conversions() {
+    check_conversions::<pasta_curves::Fp>();
+    check_conversions::<pasta_curves::Fq>();
+}
+
+#[test]
+fn bn254_conversions() {
+    check_conversions::<bn254::le::Fr>();
+    check_conversions::<bn254::be::Fr>();
+    check_conversions::<halo2curves::bn256::Fr>();
+    assert_eq!(
+        sample::<bn254::le::Fr>().to_repr().as_ref(),
+        sample::<bn254::be::Fr>().to_le_bytes()
+    );
+    assert_eq!(
+        halo2curves::bn256::Fr::MODULUS,
+        bn254::le::Fr::MODULUS.to_lowercase()
+    );
+}
+
+#[test]
+fn bls12_381_conversions() {
+    check_conversions::<bls12_381::le::Fr>();
+    check_conversions::<bls12_381::be::Fr>();
+    check_conversions::<blstrs::Scalar>();
+    assert_eq!(
+        sample::<bls12_381::le::Fr>().to_repr().as_ref(),
+        sample::<bls12_381::be::Fr>().to_le_bytes()
+    );
+    assert_eq!(
+        blstrs::Scalar::MODULUS,
+        bls12_381::le::Fr::MODULUS.to_lowercase()
+    );
+}
+
+#[test]
+fn r1cs_with_big_endian_repr() {
+    let mut le = vec![];
+    common::r1cs::<bn254::le::Fr>(32)
+        .to_writer(&mut le)
+        .unwrap();
+    let mut be = vec![];
+    common::r1cs::<bn254::be::Fr>(32)
+        .to_writer(&mut be)
+        .unwrap();
+    assert_eq!(le, be);
+
+    let read = R1CS::<bn254::be::Fr>::from_reader(std::io::Cursor::new(le)).unwrap();
+    assert_eq!(
+        read.constraints,
+        common::r1cs::<bn254::be::Fr>(32).constraints
+    );
+}
+
+#[test]
+#[cfg(feature = "circom-2")]
+fn witness_calculation_with_big_endian_repr() {
+    let mut calculator = common::circom2_copy_calculator::<bls12_381::be::Fr>(8);
+    calculator.check_prime::<bls12_381::be::Fr>().unwrap();
+
+    let input = vec![
+        sample::<bls12_381::be::Fr>(),
+        -bls12_381::be::Fr::ONE,
+        bls12_381::be::Fr::from(u64::MAX),
+    ];
+    let witness = calculator
+        .calculate_witness(vec![("in".to_string(), input.clone())], true)
+        .unwrap();
+
+    assert_eq!(witness[0], bls12_381::be::Fr::ONE);
+    assert_eq!(witness[1..4], input);
+    assert_eq!(witness[4..7], input);
+}
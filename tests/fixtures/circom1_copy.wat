@@ -0,0 +1,54 @@
+;; Hand-written witness generator following the Circom 1 runtime ABI, for the circuit
+;;
+;;   template Copy() {
+;;       signal input in[3];
+;;       signal output out[3];
+;;       for (var i = 0; i < 3; i++) out[i] <== in[i];
+;;   }
+;;
+;; over the vesta base field. Wires are `one, out[0..3], in[0..3]`, each stored as a 40 bytes
+;; field element at 1024 + 40 * wire. The constant one is stored in Montgomery form.
+(module
+  (import "env" "memory" (memory 1))
+  (import "runtime" "error" (func $error (param i32 i32 i32 i32 i32 i32)))
+
+  ;; prime
+  (data (i32.const 16) "\01\00\00\00\21\eb\46\8c\dd\a8\94\09\fc\98\46\22\00\00\00\00\00\00\00\00\00\00\00\00\00\00\00\40")
+  ;; one: short 0, long Montgomery type, 2^256 mod p
+  (data (i32.const 64) "\00\00\00\00\00\00\00\c0\fd\ff\ff\ff\9c\3e\2b\5b\67\05\42\e3\0b\35\2c\99\ff\ff\ff\ff\ff\ff\ff\ff\ff\ff\ff\ff\ff\ff\ff\3f")
+  (data (i32.const 128) "Hash not found\00")
+
+  (func (export "getFrLen") (result i32) (i32.const 40))
+  (func (export "getPRawPrime") (result i32) (i32.const 16))
+  (func (export "getNVars") (result i32) (i32.const 7))
+  (func (export "getWitnessBuffer") (result i32) (i32.const 1024))
+
+  (func (export "init") (param $sanity_check i32)
+    ;; free memory starts after the signals
+    (i32.store (i32.const 0) (i32.const 4096))
+    (memory.copy (i32.const 1024) (i32.const 64) (i32.const 40)))
+
+  (func (export "getPWitness") (param $w i32) (result i32)
+    (i32.add (i32.const 1024) (i32.mul (local.get $w) (i32.const 40))))
+
+  ;; only `in` is known, its first element is wire 4
+  (func (export "getSignalOffset32")
+    (param $p_sig_offset i32) (param $component i32) (param $hash_msb i32) (param $hash_lsb i32)
+    (if (i32.and
+          (i32.eq (local.get $hash_msb) (i32.const 0x08b73807))
+          (i32.eq (local.get $hash_lsb) (i32.const 0xb55c4bbe)))
+      (then (i32.store (local.get $p_sig_offset) (i32.const 4)))
+      (else (call $error (i32.const 3) (i32.const 128) (local.get $hash_msb) (local.get $hash_lsb)
+                         (i32.const 0) (i32.const 0)))))
+
+  ;; stores the input and propagates it to the matching output
+  (func (export "setSignal")
+    (param $c_idx i32) (param $component i32) (param $signal i32) (param $p_val i32)
+    (memory.copy
+      (i32.add (i32.const 1024) (i32.mul (local.get $signal) (i32.const 40)))
+      (local.get $p_val)
+      (i32.const 40))
+    (memory.copy
+      (i32.add (i32.const 1024) (i32.mul (i32.sub (local.get $signal) (i32.const 3)) (i32.const 40)))
+      (local.get $p_val)
+      (i32.const 40))))
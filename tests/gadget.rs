@@ -0,0 +1,148 @@
+//! The sha256 example used as a bellpepper gadget.
+#![cfg(feature = "circom-2")]
+
+mod common;
+
+use bellpepper_core::num::AllocatedNum;
+use bellpepper_core::test_cs::TestConstraintSystem;
+use bellpepper_core::{ConstraintSystem, Index, LinearCombination, SynthesisError, Variable};
+use circom_scotia::calculate_witness;
+use circom_scotia::gadget::CircomGadget;
+use ff::PrimeField;
+use pasta_curves::vesta::Base as Fr;
+
+/// A constraint system that only records the shape of a circuit, as used for parameter
+/// generation: assignments are never evaluated.
+#[derive(Default)]
+struct ShapeCs {
+    inputs: usize,
+    aux: usize,
+    constraints: usize,
+}
+
+impl<F: PrimeField> ConstraintSystem<F> for ShapeCs {
+    type Root = Self;
+
+    fn alloc<Fn, A, AR>(&mut self, _: A, _: Fn) -> Result<Variable, SynthesisError>
+    where
+        Fn: FnOnce() -> Result<F, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.aux += 1;
+        Ok(Variable::new_unchecked(Index::Aux(self.aux - 1)))
+    }
+
+    fn alloc_input<Fn, A, AR>(&mut self, _: A, _: Fn) -> Result<Variable, SynthesisError>
+    where
+        Fn: FnOnce() -> Result<F, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        // input 0 is the constant one
+        self.inputs += 1;
+        Ok(Variable::new_unchecked(Index::Input(self.inputs)))
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, _: A, _: LA, _: LB, _: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+        LB: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+        LC: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+    {
+        self.constraints += 1;
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self) {}
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+}
+
+fn gadget() -> CircomGadget<Fr> {
+    CircomGadget::new(
+        common::sha256_config()
+            .with_sym(common::SHA256_PARTIAL_SYM)
+            .unwrap(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn requires_a_symbol_table() {
+    assert!(CircomGadget::new(common::sha256_config::<Fr>()).is_err());
+}
+
+#[test]
+fn synthesize_with_values() {
+    let gadget = gadget();
+    let input = vec![Fr::from(1), Fr::from(2)];
+    let witness = calculate_witness(
+        gadget.config(),
+        vec![("arg_in".to_string(), input.clone())],
+        true,
+    )
+    .unwrap();
+
+    let mut cs = TestConstraintSystem::<Fr>::new();
+    let nums = input
+        .iter()
+        .enumerate()
+        .map(|(i, &v)| AllocatedNum::alloc(cs.namespace(|| format!("arg_in_{i}")), || Ok(v)))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let signals = gadget
+        .synthesize(&mut cs.namespace(|| "sha256"), &[("arg_in", nums)])
+        .unwrap();
+
+    assert!(cs.is_satisfied());
+    assert_eq!(signals.outputs[0].get_value(), Some(witness[1]));
+    assert_eq!(signals.named_outputs[0].name, "main.out");
+
+    // a failed witness calculation is a synthesis error
+    let mut cs = TestConstraintSystem::<Fr>::new();
+    let nums = (0..3)
+        .map(|i| AllocatedNum::alloc(cs.namespace(|| format!("arg_in_{i}")), || Ok(Fr::from(i))))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let err = gadget
+        .synthesize(&mut cs.namespace(|| "sha256"), &[("arg_in", nums)])
+        .unwrap_err();
+    assert!(matches!(err, SynthesisError::IoError(_)), "{err}");
+}
+
+#[test]
+fn synthesize_without_values() {
+    let gadget = gadget();
+    let mut cs = ShapeCs::default();
+    let nums = (0..2)
+        .map(|i| {
+            AllocatedNum::<Fr>::alloc(cs.namespace(|| format!("arg_in_{i}")), || {
+                Err(SynthesisError::AssignmentMissing)
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert!(nums.iter().all(|n| n.get_value().is_none()));
+
+    let signals = gadget
+        .synthesize(&mut cs.namespace(|| "sha256"), &[("arg_in", nums)])
+        .unwrap();
+
+    let r1cs = &gadget.config().r1cs;
+    assert_eq!(cs.constraints, r1cs.constraints.len());
+    assert_eq!(cs.inputs, 0);
+    // every wire but the constant one and the bound inputs
+    assert_eq!(cs.aux, 2 + r1cs.num_variables - 1 - 2);
+    assert!(signals.outputs[0].get_value().is_none());
+}
@@ -0,0 +1,144 @@
+//! Parsing of Circom's `input.json` format.
+
+mod common;
+
+use circom_scotia::r1cs::{CircomInput, InputError};
+#[cfg(feature = "circom-2")]
+use circom_scotia::{
+    calculate_witness, calculate_witness_from_json, r1cs::JsonWitnessError, witness::WitnessError,
+};
+use ff::Field;
+use pasta_curves::vesta::Base as Fr;
+
+fn parse(json: &str) -> Result<Vec<(String, Vec<Fr>)>, InputError> {
+    json.parse::<CircomInput<Fr>>().map(|input| input.signals)
+}
+
+fn error(json: &str) -> String {
+    parse(json).unwrap_err().to_string()
+}
+
+#[test]
+fn nested_arrays_are_flattened() {
+    let signals = parse(r#"{ "a": 1, "in": [[1, 2], [3, 4], [5, 6]] }"#).unwrap();
+    assert_eq!(
+        signals,
+        vec![
+            ("a".to_string(), vec![Fr::ONE]),
+            ("in".to_string(), (1..=6).map(Fr::from).collect()),
+        ]
+    );
+}
+
+#[test]
+fn number_formats() {
+    let signals = parse(
+        r#"{ "in": [
+            42, "42", " 42 ", "0x2a", "0X2A", "0o52", "0b101010",
+            -1, "-1", "-0",
+            "28948022309329048855892746252171976963363056481941647379679742748393362948097",
+            "0x40000000000000000000000000000000224698fc0994a8dd8c46eb2100000003"
+        ] }"#,
+    )
+    .unwrap();
+    let expected = [
+        vec![Fr::from(42); 7],
+        vec![-Fr::ONE, -Fr::ONE, Fr::ZERO],
+        // p + 0 and p + 2 are reduced
+        vec![Fr::ZERO, Fr::from(2)],
+    ]
+    .concat();
+    assert_eq!(signals, vec![("in".to_string(), expected)]);
+
+    let large = format!("\"{}\"", "9".repeat(100));
+    let signals = parse(&format!(r#"{{ "in": {large} }}"#)).unwrap();
+    let nines = Fr::from(10).pow_vartime([100]) - Fr::ONE;
+    assert_eq!(signals[0].1, vec![nines]);
+}
+
+#[test]
+fn buses_are_qualified() {
+    let signals = parse(
+        r#"{
+            "p": { "x": 1, "y": [2, 3] },
+            "ps": [{ "x": 4, "y": [5, 6] }, { "x": 7, "y": [8, 9] }],
+            "nested": { "inner": { "v": 10 } }
+        }"#,
+    )
+    .unwrap();
+    let names: Vec<_> = signals.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "nested.inner.v",
+            "p.x",
+            "p.y",
+            "ps[0].x",
+            "ps[0].y",
+            "ps[1].x",
+            "ps[1].y"
+        ]
+    );
+    assert_eq!(signals[2].1, vec![Fr::from(2), Fr::from(3)]);
+    assert_eq!(signals[6].1, vec![Fr::from(8), Fr::from(9)]);
+}
+
+#[test]
+fn errors_have_paths() {
+    assert_eq!(
+        error(r#"{ "in": [[1, 2], [3, 4], [5, 6], [7, "a"]] }"#),
+        r#"in[3][1] is not a field element: "a""#
+    );
+    assert_eq!(
+        error(r#"{ "ps": [{ "x": 1 }, { "x": [1, 1.5] }] }"#),
+        "ps[1].x[1] is not a field element: 1.5"
+    );
+    for value in [r#""""#, r#""-0x1""#, r#""1e3""#, "true", "null"] {
+        let message = error(&format!(r#"{{ "in": {value} }}"#));
+        assert_eq!(message, format!("in is not a field element: {value}"));
+    }
+    assert_eq!(
+        error(r#"{ "ps": [{ "x": 1 }, 2] }"#),
+        "ps mixes buses and field elements"
+    );
+    assert!(matches!(parse("[1, 2]"), Err(InputError::NotAnObject)));
+    assert!(matches!(parse("{"), Err(InputError::Json(_))));
+}
+
+#[test]
+#[cfg(feature = "circom-2")]
+fn witness_from_json() {
+    let cfg = common::sha256_config::<Fr>();
+
+    let input = r#"{ "arg_in": ["0x1", 2] }"#;
+    let witness = calculate_witness_from_json(&cfg, input.as_bytes()).unwrap();
+    let expected = calculate_witness(
+        &cfg,
+        vec![("arg_in".to_string(), vec![Fr::ONE, Fr::from(2)])],
+        true,
+    )
+    .unwrap();
+    assert_eq!(witness, expected);
+    assert!(cfg.check(&witness).unwrap().is_empty());
+
+    let err = calculate_witness_from_json(&cfg, r#"{ "arg_in": [0, 0, 0] }"#.as_bytes());
+    assert!(
+        matches!(
+            err,
+            Err(JsonWitnessError::Witness(WitnessError::WrongInputLength {
+                expected: 2,
+                found: 3,
+                ..
+            }))
+        ),
+        "{err:?}"
+    );
+    let err = calculate_witness_from_json(&cfg, r#"{ "arg_in": [0, "x"] }"#.as_bytes());
+    assert!(
+        matches!(
+            err,
+            Err(JsonWitnessError::Input(InputError::NotAFieldElement { .. }))
+        ),
+        "{err:?}"
+    );
+}
@@ -0,0 +1,68 @@
+//! Concurrent witness calculation with a pool of instances.
+
+mod common;
+
+#[cfg(feature = "circom-2")]
+use circom_scotia::calculate_witness;
+use circom_scotia::witness::WitnessCalculatorPool;
+use pasta_curves::vesta::Base as Fr;
+
+const COPY_WAT: &str = include_str!("fixtures/circom1_copy.wat");
+
+#[test]
+fn pool_recovers_from_failed_runs() {
+    let pool = WitnessCalculatorPool::new(common::calculator(COPY_WAT), 2).unwrap();
+    let inputs = (0..8u64).map(|i| {
+        // every third input names an unknown signal
+        let name = if i % 3 == 0 { "inn" } else { "in" };
+        vec![(
+            name.to_string(),
+            vec![Fr::from(i), -Fr::from(i), Fr::from(u64::MAX - i)],
+        )]
+    });
+
+    let witnesses = pool.calculate_witnesses(inputs, true);
+
+    assert_eq!(witnesses.len(), 8);
+    for (i, witness) in (0..8u64).zip(witnesses) {
+        if i % 3 == 0 {
+            assert!(witness.is_err());
+        } else {
+            let witness = witness.unwrap();
+            assert_eq!(witness[1], Fr::from(i));
+            assert_eq!(witness[6], Fr::from(u64::MAX - i));
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "circom-2")]
+fn sha256_pool_matches_sequential_runs() {
+    let cfg = common::sha256_config::<Fr>();
+    let pool = WitnessCalculatorPool::from_file("examples/sha256/circom_sha256.wasm", 3).unwrap();
+    assert_eq!(pool.size(), 3);
+
+    let inputs: Vec<_> = (0..6u64)
+        .map(|i| vec![("arg_in".to_string(), vec![Fr::from(i), Fr::from(2 * i + 1)])])
+        .collect();
+    let expected: Vec<_> = inputs
+        .iter()
+        .map(|input| calculate_witness(&cfg, input.clone(), true).unwrap())
+        .collect();
+
+    let witnesses: Vec<_> = pool
+        .calculate_witnesses(inputs.clone(), true)
+        .into_iter()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(witnesses, expected);
+    assert!(witnesses
+        .iter()
+        .all(|witness| cfg.check(witness).unwrap().is_empty()));
+
+    // a single witness on the shared pool
+    assert_eq!(
+        pool.calculate_witness(inputs[4].clone(), true).unwrap(),
+        expected[4]
+    );
+}
@@ -0,0 +1,132 @@
+//! Checking witnesses against an r1cs.
+
+mod common;
+
+#[cfg(feature = "circom-2")]
+use circom_scotia::calculate_witness;
+use circom_scotia::r1cs::CheckError;
+use circom_scotia::sym::SymbolTable;
+use ff::Field;
+use pasta_curves::vesta::Base as Fr;
+
+#[test]
+fn unsatisfied_constraints() {
+    let r1cs = common::copy_r1cs::<Fr>(32);
+    let mut witness = [1, 2, 3, 4, 2, 3, 4].map(Fr::from).to_vec();
+    assert!(r1cs.check(&witness).unwrap().is_empty());
+
+    witness[5] = Fr::ZERO;
+    let unsatisfied = r1cs.check(&witness).unwrap();
+    assert_eq!(unsatisfied.len(), 1);
+    assert_eq!(unsatisfied[0].index, 1);
+    assert_eq!(
+        (unsatisfied[0].a, unsatisfied[0].b, unsatisfied[0].c),
+        (Fr::ZERO, Fr::ONE, Fr::from(3))
+    );
+}
+
+#[test]
+fn short_witness() {
+    let r1cs = common::copy_r1cs::<Fr>(32);
+    let err = r1cs.check(&[Fr::ONE; 6]).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            CheckError::WitnessLength {
+                expected: 7,
+                found: 6
+            }
+        ),
+        "{err}"
+    );
+}
+
+#[test]
+fn wire_out_of_range() {
+    let mut r1cs = common::copy_r1cs::<Fr>(32);
+    r1cs.constraints[2].0[0].0 = 9;
+    let err = r1cs.check(&[Fr::ONE; 7]).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            CheckError::WireOutOfRange {
+                constraint: 2,
+                wire: 9,
+                wires: 7
+            }
+        ),
+        "{err}"
+    );
+}
+
+#[test]
+#[cfg(feature = "circom-2")]
+fn labels_and_wires() {
+    let cfg = common::sha256_config::<Fr>();
+    let r1cs = &cfg.r1cs;
+    assert!(r1cs.wire_mapping.iter().enumerate().any(|(w, &l)| w != l));
+    let witness = calculate_witness(
+        &cfg,
+        vec![("arg_in".to_string(), vec![Fr::from(1), Fr::from(2)])],
+        true,
+    )
+    .unwrap();
+
+    let labels = r1cs.labels_from_wires(&witness);
+    assert_eq!(labels.len(), r1cs.num_labels);
+    for (wire, &label) in r1cs.wire_mapping.iter().enumerate() {
+        assert_eq!(labels[label], Some(witness[wire]));
+    }
+    // labels optimized away have no value
+    let missing = labels.iter().filter(|l| l.is_none()).count();
+    assert_eq!(missing, r1cs.num_labels - r1cs.num_variables);
+    assert!(missing > 0);
+
+    // round trip, whatever the values of the missing labels
+    let labels: Vec<_> = labels.into_iter().map(|l| l.unwrap_or(Fr::ZERO)).collect();
+    assert_eq!(r1cs.wires_from_labels(&labels), Some(witness));
+
+    // too short for the last labels
+    assert_eq!(r1cs.wires_from_labels(&labels[..labels.len() / 2]), None);
+}
+
+#[test]
+fn unsatisfied_signals_are_named() {
+    let r1cs = common::copy_r1cs::<Fr>(32);
+    let sym = SymbolTable::from_reader(
+        "1,1,0,main.out[0]\n2,2,0,main.out[1]\n3,3,0,main.out[2]\n\
+         4,4,0,main.in[0]\n5,5,0,main.in[1]\n6,6,0,main.in[2]\n"
+            .as_bytes(),
+    )
+    .unwrap();
+    let mut witness = [1, 2, 3, 4, 2, 3, 4].map(Fr::from).to_vec();
+    witness[2] = Fr::ZERO;
+
+    let unsatisfied = r1cs.check_with_sym(&witness, &sym).unwrap();
+    assert_eq!(unsatisfied.len(), 1);
+    // by wire, the constant one has no name
+    assert_eq!(unsatisfied[0].signals, ["main.out[1]", "main.in[1]"]);
+    assert!(r1cs.check(&witness).unwrap()[0].signals.is_empty());
+}
+
+#[test]
+#[cfg(feature = "circom-2")]
+fn config_check_names_signals() {
+    let cfg = common::sha256_config::<Fr>()
+        .with_sym(common::SHA256_PARTIAL_SYM)
+        .unwrap();
+    let mut witness = calculate_witness(
+        &cfg,
+        vec![("arg_in".to_string(), vec![Fr::from(1), Fr::from(2)])],
+        true,
+    )
+    .unwrap();
+    assert!(cfg.check(&witness).unwrap().is_empty());
+
+    witness[1] += Fr::ONE;
+    let unsatisfied = cfg.check(&witness).unwrap();
+    assert!(!unsatisfied.is_empty());
+    assert!(unsatisfied
+        .iter()
+        .all(|c| c.signals.iter().any(|name| name == "main.out")));
+}
@@ -0,0 +1,234 @@
+//! Loading of malformed r1cs files, which must fail with an error rather than panic or abort.
+
+mod common;
+
+use std::io::Cursor;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use circom_scotia::r1cs::R1CS;
+use circom_scotia::reader::{try_load_r1cs, ReaderError};
+use pasta_curves::vesta::Base as Fr;
+
+/// Offsets of the wire count and the constraint count in the header section of a 32-byte field.
+const N_WIRES: usize = 4 + 32;
+const N_PUB_OUT: usize = N_WIRES + 4;
+const N_CONSTRAINTS: usize = N_WIRES + 24;
+
+fn r1cs_bytes() -> Vec<u8> {
+    let mut bytes = vec![];
+    common::r1cs::<Fr>(32).to_writer(&mut bytes).unwrap();
+    bytes
+}
+
+/// Offset and size of the content of the section of type `section`.
+fn section(bytes: &[u8], section: u32) -> (usize, usize) {
+    let mut pos = 12;
+    loop {
+        let kind = u32::from_le_bytes(bytes[pos..pos + 4].try_into().unwrap());
+        let size = u64::from_le_bytes(bytes[pos + 4..pos + 12].try_into().unwrap()) as usize;
+        if kind == section {
+            return (pos + 12, size);
+        }
+        pos += 12 + size;
+    }
+}
+
+fn patch_header(bytes: &mut [u8], offset: usize, value: u32) {
+    let (header, _) = section(bytes, 1);
+    bytes[header + offset..header + offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+fn read(bytes: Vec<u8>) -> Result<R1CS<Fr>, ReaderError> {
+    R1CS::from_reader(Cursor::new(bytes))
+}
+
+#[test]
+fn valid_file() {
+    assert_eq!(
+        read(r1cs_bytes()).unwrap().constraints,
+        common::r1cs::<Fr>(32).constraints
+    );
+}
+
+#[test]
+fn bad_magic() {
+    let mut bytes = r1cs_bytes();
+    bytes[0] ^= 0xff;
+    let err = read(bytes).unwrap_err();
+    assert!(matches!(err, ReaderError::BadMagic { .. }), "{err}");
+}
+
+#[test]
+fn unsupported_version() {
+    let mut bytes = r1cs_bytes();
+    bytes[4..8].copy_from_slice(&2u32.to_le_bytes());
+    let err = read(bytes).unwrap_err();
+    assert!(matches!(err, ReaderError::UnsupportedVersion(2)), "{err}");
+}
+
+#[test]
+fn missing_section() {
+    let mut bytes = r1cs_bytes();
+    let (map, size) = section(&bytes, 3);
+    bytes.drain(map - 12..map + size);
+    bytes[8..12].copy_from_slice(&2u32.to_le_bytes());
+    let err = read(bytes).unwrap_err();
+    assert!(matches!(err, ReaderError::MissingSection(3)), "{err}");
+}
+
+#[test]
+fn prime_mismatch() {
+    let err = R1CS::<pasta_curves::Fp>::from_reader(Cursor::new(r1cs_bytes())).unwrap_err();
+    assert!(matches!(err, ReaderError::PrimeMismatch { .. }), "{err}");
+}
+
+#[test]
+fn bad_header_size() {
+    // the field size no longer matches the size of the header section
+    let mut bytes = r1cs_bytes();
+    patch_header(&mut bytes, 0, 33);
+    let err = read(bytes).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ReaderError::BadSectionSize {
+                section: 1,
+                expected: 65,
+                found: 64
+            }
+        ),
+        "{err}"
+    );
+}
+
+#[test]
+fn coefficient_out_of_range() {
+    // first coefficient of the first linear combination, after the term count and the wire
+    let mut bytes = r1cs_bytes();
+    let (constraints, _) = section(&bytes, 2);
+    let coeff = constraints + 8;
+    bytes[coeff..coeff + 32].copy_from_slice(&common::modulus_le_bytes::<Fr>(32));
+    let err = read(bytes).unwrap_err();
+    assert!(matches!(err, ReaderError::BadFieldElement(_)), "{err}");
+}
+
+#[test]
+fn fewer_wires_than_public_signals() {
+    let mut bytes = r1cs_bytes();
+    patch_header(&mut bytes, N_PUB_OUT, 5);
+    let err = read(bytes).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ReaderError::TooFewWires {
+                wires: 3,
+                public: 5
+            }
+        ),
+        "{err}"
+    );
+
+    let mut bytes = r1cs_bytes();
+    patch_header(&mut bytes, N_PUB_OUT, u32::MAX);
+    let err = read(bytes).unwrap_err();
+    assert!(matches!(err, ReaderError::TooFewWires { .. }), "{err}");
+}
+
+#[test]
+fn huge_counts_are_not_preallocated() {
+    let mut bytes = r1cs_bytes();
+    patch_header(&mut bytes, N_CONSTRAINTS, u32::MAX);
+    assert!(read(bytes).is_err());
+
+    // number of terms of the first linear combination
+    let mut bytes = r1cs_bytes();
+    let (constraints, _) = section(&bytes, 2);
+    bytes[constraints..constraints + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(read(bytes).is_err());
+}
+
+#[test]
+fn sections_past_the_end() {
+    let mut bytes = r1cs_bytes();
+    // size of the first section, the constraints
+    bytes[16..24].copy_from_slice(&u64::MAX.to_le_bytes());
+    let err = read(bytes).unwrap_err();
+    assert!(matches!(err, ReaderError::TruncatedSection(2)), "{err}");
+}
+
+#[test]
+fn wire_out_of_range() {
+    let mut bytes = r1cs_bytes();
+    patch_header(&mut bytes, N_WIRES, 2);
+    // keep the wire map consistent with the header
+    let (map, size) = section(&bytes, 3);
+    bytes.drain(map + 16..map + size);
+    bytes[map - 8..map].copy_from_slice(&16u64.to_le_bytes());
+    let err = read(bytes).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ReaderError::WireOutOfRange {
+                constraint: 0,
+                wire: 2,
+                wires: 2
+            }
+        ),
+        "{err}"
+    );
+}
+
+fn read_json(json: &str) -> Result<R1CS<Fr>, ReaderError> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let path = std::env::temp_dir().join(format!(
+        "circom-scotia-reader-{}-{}.json",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::write(&path, json).unwrap();
+    let r1cs = try_load_r1cs(&path);
+    std::fs::remove_file(&path).unwrap();
+    r1cs
+}
+
+#[test]
+fn json_files() {
+    let json = |constraints: &str, vars: usize| {
+        format!(
+            r#"{{ "constraints": {constraints}, "nPubInputs": 0, "nOutputs": 1, "nVars": {vars} }}"#
+        )
+    };
+
+    let r1cs = read_json(&json(r#"[[{ "2": "3" }, { "0": "1" }, { "1": "3" }]]"#, 3)).unwrap();
+    assert_eq!(r1cs.num_aux, 1);
+
+    let err = read_json(&json("[]", 1)).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ReaderError::TooFewWires {
+                wires: 1,
+                public: 1
+            }
+        ),
+        "{err}"
+    );
+
+    let err = read_json(&json(r#"[[{ "2": "3" }, { "0": "1" }]]"#, 3)).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ReaderError::BadConstraint {
+                constraint: 0,
+                found: 2
+            }
+        ),
+        "{err}"
+    );
+
+    let err = read_json(&json(r#"[[{ "3": "3" }, { "0": "1" }, { "1": "3" }]]"#, 3)).unwrap_err();
+    assert!(
+        matches!(err, ReaderError::WireOutOfRange { wire: 3, .. }),
+        "{err}"
+    );
+}
@@ -0,0 +1,128 @@
+//! Fields encoded on fewer bytes than their repr, with the Goldilocks field which Circom stores on
+//! 8 bytes while `ff` derives a 16-byte repr for it.
+
+mod common;
+
+use circom_scotia::r1cs::R1CS;
+use circom_scotia::writer::write_witness_bin;
+use ff::{Field, PrimeField};
+
+#[derive(PrimeField)]
+#[PrimeFieldModulus = "18446744069414584321"]
+#[PrimeFieldGenerator = "7"]
+#[PrimeFieldReprEndianness = "little"]
+struct Goldilocks([u64; 2]);
+
+/// The iden3 encoding of `common::r1cs`, with elements on `field_size` bytes.
+fn r1cs_bytes(field_size: usize) -> Vec<u8> {
+    let r1cs = common::r1cs::<Goldilocks>(8);
+    let field = |f: &Goldilocks| {
+        let mut bytes = f.to_repr().as_ref().to_vec();
+        bytes.resize(field_size, 0);
+        bytes
+    };
+
+    let mut constraints = vec![];
+    for (a, b, c) in &r1cs.constraints {
+        for lc in [a, b, c] {
+            constraints.extend((lc.len() as u32).to_le_bytes());
+            for (index, coeff) in lc {
+                constraints.extend((*index as u32).to_le_bytes());
+                constraints.extend(field(coeff));
+            }
+        }
+    }
+
+    let mut header = (field_size as u32).to_le_bytes().to_vec();
+    header.extend(common::modulus_le_bytes::<Goldilocks>(field_size));
+    for n in [3u32, 1, 0, 1] {
+        header.extend(n.to_le_bytes());
+    }
+    header.extend(3u64.to_le_bytes());
+    header.extend(1u32.to_le_bytes());
+
+    let map: Vec<u8> = (0..3u64).flat_map(u64::to_le_bytes).collect();
+
+    let mut bytes = b"r1cs".to_vec();
+    bytes.extend(1u32.to_le_bytes());
+    bytes.extend(3u32.to_le_bytes());
+    for (section, content) in [(2u32, constraints), (1, header), (3, map)] {
+        bytes.extend(section.to_le_bytes());
+        bytes.extend((content.len() as u64).to_le_bytes());
+        bytes.extend(content);
+    }
+    bytes
+}
+
+#[test]
+fn r1cs_round_trip() {
+    let r1cs = common::r1cs::<Goldilocks>(8);
+    let mut bytes = vec![];
+    r1cs.to_writer(&mut bytes).unwrap();
+    assert_eq!(bytes, r1cs_bytes(8));
+
+    let read = R1CS::<Goldilocks>::from_reader(std::io::Cursor::new(bytes)).unwrap();
+    assert_eq!(read.field_size, 8);
+    assert_eq!(read.prime, r1cs.prime);
+    assert_eq!(read.constraints, r1cs.constraints);
+}
+
+#[test]
+fn r1cs_with_wider_elements() {
+    for field_size in [16, 32] {
+        let read =
+            R1CS::<Goldilocks>::from_reader(std::io::Cursor::new(r1cs_bytes(field_size))).unwrap();
+        assert_eq!(read.field_size, field_size);
+        assert_eq!(read.constraints, common::r1cs::<Goldilocks>(8).constraints);
+    }
+}
+
+#[test]
+fn r1cs_with_narrower_elements() {
+    let read = R1CS::<Goldilocks>::from_reader(std::io::Cursor::new(r1cs_bytes(4)));
+    assert!(read.is_err());
+}
+
+#[test]
+fn witness_bin_uses_8_byte_elements() {
+    let witness = vec![
+        Goldilocks::ONE,
+        common::large::<Goldilocks>(),
+        -Goldilocks::ONE,
+    ];
+    let mut bytes = vec![];
+    write_witness_bin(&witness, &mut bytes).unwrap();
+
+    // magic, version and number of sections, then the header section
+    assert_eq!(&bytes[24..28], &8u32.to_le_bytes());
+    assert_eq!(
+        &bytes[28..36],
+        &common::modulus_le_bytes::<Goldilocks>(8)[..]
+    );
+    // witness length, then the witness section
+    assert_eq!(bytes.len(), 40 + 12 + 3 * 8);
+    assert_eq!(
+        &bytes[52 + 8..52 + 16],
+        &common::large::<Goldilocks>().to_repr().as_ref()[..8]
+    );
+}
+
+#[test]
+#[cfg(feature = "circom-2")]
+fn witness_calculation() {
+    let mut calculator = common::circom2_copy_calculator::<Goldilocks>(2);
+    calculator.check_prime::<Goldilocks>().unwrap();
+
+    let input = vec![
+        common::large::<Goldilocks>(),
+        -Goldilocks::ONE,
+        Goldilocks::from(u64::from(u32::MAX) + 1),
+    ];
+    let witness = calculator
+        .calculate_witness(vec![("in".to_string(), input.clone())], true)
+        .unwrap();
+
+    assert_eq!(witness[0], Goldilocks::ONE);
+    assert_eq!(witness[1..4], input);
+    assert_eq!(witness[4..7], input);
+}
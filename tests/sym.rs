@@ -0,0 +1,165 @@
+//! Parsing of Circom `.sym` files.
+//!
+//! `fixtures/sha256_partial.sym` is a hand-written symbol table for the sha256 example, not the
+//! one emitted by the compiler: it only covers the first 13 of the 204154 labels. The signals of
+//! the main component and the wires of its labels are those of the example, the names of the
+//! signals of the subcomponents are illustrative.
+
+mod common;
+
+use std::io::Cursor;
+
+#[cfg(feature = "circom-2")]
+use circom_scotia::r1cs::InputSignal;
+use circom_scotia::r1cs::R1CS;
+use circom_scotia::reader::ReaderError;
+use circom_scotia::sym::SymbolTable;
+use pasta_curves::vesta::Base as Fr;
+
+use common::SHA256_PARTIAL_SYM;
+
+fn parse(sym: &str) -> Result<SymbolTable, ReaderError> {
+    SymbolTable::from_reader(Cursor::new(sym))
+}
+
+#[test]
+fn sha256_symbols() {
+    let sym = SymbolTable::from_file(SHA256_PARTIAL_SYM).unwrap();
+    assert_eq!(sym.symbols().len(), 13);
+
+    let out = sym.get("main.out").unwrap();
+    assert_eq!((out.label, out.wire, out.component), (1, Some(1), 0));
+    assert_eq!(sym.wire("main.arg_in[1]"), Some(3));
+    assert_eq!(sym.by_wire(4).unwrap().name, "main.sha.out[0]");
+
+    // optimized away
+    let input = sym.get("main.sha.in[2]").unwrap();
+    assert_eq!((input.label, input.wire), (6, None));
+    assert_eq!(sym.wire("main.sha.in[2]"), None);
+    assert_eq!(sym.wire("main.unknown"), None);
+
+    // the fixture agrees with the r1cs
+    let bytes = std::fs::read("examples/sha256/circom_sha256.r1cs").unwrap();
+    let r1cs = R1CS::<Fr>::from_reader(Cursor::new(bytes)).unwrap();
+    for symbol in sym.symbols() {
+        match symbol.wire {
+            Some(wire) => assert_eq!(r1cs.wire_mapping[wire], symbol.label),
+            None => assert!(!r1cs.wire_mapping.contains(&symbol.label)),
+        }
+    }
+}
+
+#[test]
+fn values() {
+    let sym = SymbolTable::from_file(SHA256_PARTIAL_SYM).unwrap();
+    let witness = [10, 11, 12, 13, 14].map(Fr::from);
+    assert_eq!(sym.value(&witness, "main.arg_in[0]"), Some(Fr::from(12)));
+    assert_eq!(sym.value(&witness, "main.sha.in[0]"), None);
+    assert_eq!(sym.value(&witness, "main.sha.n2b.out[0]"), None);
+}
+
+#[test]
+fn malformed_lines() {
+    let sym = parse("1,1,0,main.out\n\n  \n2, 2 ,0, main.in \n").unwrap();
+    assert_eq!(sym.wire("main.in"), Some(2));
+
+    let cases = [
+        ("1,1,0", 1),
+        ("1,1,0,main.out\nx,2,0,main.in", 2),
+        ("1,1,0,main.out\n\n2,-2,0,main.in", 3),
+        ("-1,1,0,main.out", 1),
+        ("1,1,-1,main.out", 1),
+        ("1,1,0.5,main.out", 1),
+    ];
+    for (content, line) in cases {
+        let err = parse(content).unwrap_err();
+        assert!(
+            matches!(err, ReaderError::InvalidSymbol { line: l, .. } if l == line),
+            "{content:?}: {err}"
+        );
+    }
+}
+
+#[test]
+fn base_name_and_indices() {
+    let sym = parse(
+        "1,1,0,main.out\n\
+         2,2,1,main.sha.out[3]\n\
+         3,3,2,main.c[1].in[2][10]\n",
+    )
+    .unwrap();
+    let names: Vec<_> = sym
+        .symbols()
+        .iter()
+        .map(|s| (s.base_name(), s.indices()))
+        .collect();
+    assert_eq!(
+        names,
+        [
+            ("main.out", vec![]),
+            ("main.sha.out", vec![3]),
+            ("main.c[1].in", vec![2, 10]),
+        ]
+    );
+}
+
+#[test]
+fn signal_wires() {
+    let sym = SymbolTable::from_file(SHA256_PARTIAL_SYM).unwrap();
+    assert_eq!(sym.signal_wires("main.arg_in"), Some(vec![2, 3]));
+    assert_eq!(sym.signal_wires("main.sha.out"), Some(vec![4, 5, 6, 7]));
+    assert_eq!(sym.signal_wires("main.sha.in"), None);
+    assert_eq!(sym.signal_wires("main.unknown"), None);
+
+    // row-major order, whatever the order of the file
+    let sym = parse(
+        "1,4,0,main.m[1][0]\n\
+         2,2,0,main.m[0][1]\n\
+         3,1,0,main.m[0][0]\n\
+         4,5,0,main.m[1][1]\n\
+         5,3,0,main.m[0][2]\n\
+         6,6,0,main.m[1][2]\n",
+    )
+    .unwrap();
+    assert_eq!(sym.signal_wires("main.m"), Some(vec![1, 2, 3, 4, 5, 6]));
+}
+
+#[test]
+fn component_signals() {
+    let sym = SymbolTable::from_file(SHA256_PARTIAL_SYM).unwrap();
+    let names = |component| {
+        sym.component_signals(component)
+            .map(|s| s.name.as_str())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        names("main"),
+        ["main.out", "main.arg_in[0]", "main.arg_in[1]"]
+    );
+    assert_eq!(names("main.sha").len(), 8);
+    assert!(names("main.sha").iter().all(|n| !n.contains("n2b")));
+    assert_eq!(names("main.sha.n2b").len(), 2);
+    assert!(names("main.sh").is_empty());
+}
+
+#[test]
+#[cfg(feature = "circom-2")]
+fn sha256_input_signals() {
+    let cfg = common::sha256_config::<Fr>()
+        .with_sym(SHA256_PARTIAL_SYM)
+        .unwrap();
+
+    assert_eq!(
+        cfg.input_signals(),
+        [InputSignal {
+            name: "arg_in".to_string(),
+            shape: vec![2],
+            size: 2,
+            public: true,
+        }]
+    );
+    assert_eq!(
+        cfg.input_json_skeleton(),
+        serde_json::json!({ "arg_in": ["0", "0"] })
+    );
+}
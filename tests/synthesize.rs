@@ -0,0 +1,125 @@
+//! Synthesis of a Circom circuit with its inputs bound to existing variables.
+#![cfg(feature = "circom-2")]
+
+mod common;
+
+use bellpepper_core::num::AllocatedNum;
+use bellpepper_core::test_cs::TestConstraintSystem;
+use bellpepper_core::{ConstraintSystem, SynthesisError};
+use circom_scotia::r1cs::CircomConfig;
+use circom_scotia::{calculate_witness, synthesize_with_inputs};
+use pasta_curves::vesta::Base as Fr;
+
+fn config() -> CircomConfig<Fr> {
+    common::sha256_config()
+        .with_sym(common::SHA256_PARTIAL_SYM)
+        .unwrap()
+}
+
+fn alloc(cs: &mut TestConstraintSystem<Fr>, values: &[u64]) -> Vec<AllocatedNum<Fr>> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, &v)| {
+            AllocatedNum::alloc(cs.namespace(|| format!("arg_in_{i}")), || Ok(Fr::from(v))).unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn inputs_are_bound() {
+    let cfg = config();
+    let sym = cfg.sym.as_ref().unwrap();
+    let witness = calculate_witness(
+        &cfg,
+        vec![("arg_in".to_string(), vec![Fr::from(1), Fr::from(2)])],
+        true,
+    )
+    .unwrap();
+
+    let mut cs = TestConstraintSystem::<Fr>::new();
+    let nums = alloc(&mut cs, &[1, 2]);
+    let signals = synthesize_with_inputs(
+        &mut cs.namespace(|| "sha256"),
+        &cfg.r1cs,
+        sym,
+        &[("arg_in", nums.clone())],
+        Some(witness.clone()),
+    )
+    .unwrap();
+
+    assert!(cs.is_satisfied());
+    assert_eq!(cs.num_constraints(), cfg.r1cs.constraints.len());
+    for (input, num) in signals.inputs.iter().zip(&nums) {
+        assert_eq!(input.get_variable(), num.get_variable());
+    }
+    assert_eq!(signals.named_inputs[0].name, "main.arg_in");
+    assert_eq!(signals.named_inputs[0].values.len(), 2);
+    assert_eq!(signals.outputs[0].get_value(), Some(witness[1]));
+    assert_eq!(signals.named_outputs[0].name, "main.out");
+}
+
+#[test]
+fn bound_inputs_are_constrained() {
+    let cfg = config();
+    let witness = calculate_witness(
+        &cfg,
+        vec![("arg_in".to_string(), vec![Fr::from(1), Fr::from(2)])],
+        true,
+    )
+    .unwrap();
+
+    // the variables do not hold the values the witness was calculated for
+    let mut cs = TestConstraintSystem::<Fr>::new();
+    let nums = alloc(&mut cs, &[1, 3]);
+    synthesize_with_inputs(
+        &mut cs.namespace(|| "sha256"),
+        &cfg.r1cs,
+        cfg.sym.as_ref().unwrap(),
+        &[("arg_in", nums)],
+        Some(witness),
+    )
+    .unwrap();
+
+    assert!(!cs.is_satisfied());
+}
+
+#[test]
+fn input_errors() {
+    let cfg = config();
+    let sym = cfg.sym.as_ref().unwrap();
+    let mut cs = TestConstraintSystem::<Fr>::new();
+    let nums = alloc(&mut cs, &[1, 2, 3]);
+
+    let cases = [
+        (
+            "arg_in",
+            &nums[..1],
+            "input signal arg_in has 2 elements, 1 given",
+        ),
+        (
+            "arg_in",
+            &nums[..],
+            "input signal arg_in has 2 elements, 3 given",
+        ),
+        (
+            "arg",
+            &nums[..2],
+            "input signal arg not found in the symbol table",
+        ),
+    ];
+    for (i, (name, nums, message)) in cases.into_iter().enumerate() {
+        let err = synthesize_with_inputs(
+            &mut cs.namespace(|| format!("case_{i}")),
+            &cfg.r1cs,
+            sym,
+            &[(name, nums.to_vec())],
+            None,
+        )
+        .unwrap_err();
+        assert!(
+            matches!(&err, SynthesisError::IncompatibleLengthVector(m) if m == message),
+            "{err}"
+        );
+    }
+}
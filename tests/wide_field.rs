@@ -0,0 +1,43 @@
+//! Fields wider than 256 bits, with the BLS12-381 base field.
+
+mod common;
+
+use circom_scotia::r1cs::R1CS;
+#[cfg(feature = "circom-2")]
+use ff::Field;
+use ff::PrimeField;
+
+#[derive(PrimeField)]
+#[PrimeFieldModulus = "4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559787"]
+#[PrimeFieldGenerator = "2"]
+#[PrimeFieldReprEndianness = "little"]
+struct Fp([u64; 6]);
+
+#[test]
+fn r1cs_round_trip() {
+    let r1cs = common::r1cs::<Fp>(48);
+
+    let mut bytes = vec![];
+    r1cs.to_writer(&mut bytes).unwrap();
+    let read = R1CS::<Fp>::from_reader(std::io::Cursor::new(bytes)).unwrap();
+
+    assert_eq!(read.field_size, 48);
+    assert_eq!(read.prime, r1cs.prime);
+    assert_eq!(read.constraints, r1cs.constraints);
+}
+
+#[test]
+#[cfg(feature = "circom-2")]
+fn witness_calculation() {
+    let mut calculator = common::circom2_copy_calculator::<Fp>(12);
+    calculator.check_prime::<Fp>().unwrap();
+
+    let input = vec![common::large(), -Fp::ONE, Fp::from(u64::MAX)];
+    let witness = calculator
+        .calculate_witness(vec![("in".to_string(), input.clone())], true)
+        .unwrap();
+
+    assert_eq!(witness[0], Fp::ONE);
+    assert_eq!(witness[1..4], input);
+    assert_eq!(witness[4..7], input);
+}
@@ -0,0 +1,132 @@
+//! Writers of the iden3 binary formats.
+
+mod common;
+
+use std::fs;
+use std::io::{Cursor, ErrorKind};
+
+use circom_scotia::r1cs::R1CS;
+use circom_scotia::reader::load_witness_from_bin_reader;
+use circom_scotia::writer::{
+    write_witness_bin, write_witness_json, WitnessBinWriter, WitnessJsonWriter,
+};
+use ff::{Field, PrimeField};
+use pasta_curves::vesta::Base as Fr;
+
+fn witness() -> Vec<Fr> {
+    vec![Fr::ONE, Fr::from(42), -Fr::ONE, Fr::from(u64::MAX).square()]
+}
+
+#[test]
+fn r1cs_round_trip_is_byte_for_byte() {
+    let bytes = fs::read("examples/sha256/circom_sha256.r1cs").unwrap();
+    let r1cs = R1CS::<Fr>::from_reader(Cursor::new(&bytes)).unwrap();
+
+    let mut written = vec![];
+    r1cs.to_writer(&mut written).unwrap();
+    assert_eq!(written.len(), bytes.len());
+    assert!(written == bytes);
+}
+
+#[test]
+fn r1cs_keeps_its_field_size() {
+    // elements on 40 bytes instead of 32, as accepted by the reader
+    let mut bytes = vec![];
+    common::r1cs::<Fr>(40).to_writer(&mut bytes).unwrap();
+    let r1cs = R1CS::<Fr>::from_reader(Cursor::new(&bytes)).unwrap();
+    assert_eq!(r1cs.field_size, 40);
+
+    let mut written = vec![];
+    r1cs.to_writer(&mut written).unwrap();
+    assert!(written == bytes);
+}
+
+#[test]
+fn r1cs_writer_errors() {
+    let error = |r1cs: R1CS<Fr>| r1cs.to_writer(&mut vec![]).unwrap_err();
+
+    // too small for the field
+    let mut r1cs = common::r1cs::<Fr>(32);
+    r1cs.field_size = 16;
+    r1cs.prime.truncate(16);
+    assert_eq!(error(r1cs).kind(), ErrorKind::InvalidInput);
+
+    let mut r1cs = common::r1cs::<Fr>(32);
+    r1cs.prime[0] ^= 1;
+    assert_eq!(error(r1cs).kind(), ErrorKind::InvalidInput);
+
+    // counts and wires that do not fit the format
+    let mut r1cs = common::r1cs::<Fr>(32);
+    r1cs.num_variables = 1 << 32;
+    let err = error(r1cs);
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert_eq!(
+        err.to_string(),
+        "number of wires 4294967296 does not fit in 32 bits"
+    );
+
+    let mut r1cs = common::r1cs::<Fr>(32);
+    r1cs.constraints[0].0[0].0 = 1 << 32;
+    assert_eq!(error(r1cs).kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn witness_bin_round_trip() {
+    let witness = witness();
+    let mut bytes = vec![];
+    write_witness_bin(&witness, &mut bytes).unwrap();
+    assert_eq!(bytes.len(), 12 + 12 + 4 + 32 + 4 + 12 + witness.len() * 32);
+    assert_eq!(
+        load_witness_from_bin_reader::<Fr, _>(bytes.as_slice()).unwrap(),
+        witness
+    );
+
+    // streamed one element at a time
+    let mut writer = WitnessBinWriter::<Fr, _>::new(vec![], witness.len()).unwrap();
+    for f in &witness {
+        writer.write(f).unwrap();
+    }
+    assert_eq!(writer.finish().unwrap(), bytes);
+}
+
+#[test]
+fn witness_bin_length_is_checked() {
+    // missing elements
+    let writer = WitnessBinWriter::<Fr, _>::new(vec![], 1).unwrap();
+    assert!(writer.finish().is_err());
+
+    // too many elements
+    let mut writer = WitnessBinWriter::<Fr, _>::new(vec![], 1).unwrap();
+    writer.write(&Fr::ONE).unwrap();
+    assert!(writer.write(&Fr::ONE).is_err());
+}
+
+#[test]
+fn witness_json_has_the_snarkjs_layout() {
+    let witness = witness();
+    let mut bytes = vec![];
+    write_witness_json(&witness, &mut bytes).unwrap();
+
+    let p_minus_one =
+        "28948022309329048855892746252171976963363056481941647379679742748393362948096";
+    let expected = format!(
+        "[\n \"1\",\n \"42\",\n \"{p_minus_one}\",\n \"340282366920938463426481119284349108225\"\n]"
+    );
+    assert_eq!(String::from_utf8(bytes.clone()).unwrap(), expected);
+
+    let read: Vec<String> = serde_json::from_slice(&bytes).unwrap();
+    let read: Vec<Fr> = read
+        .iter()
+        .map(|s| Fr::from_str_vartime(s).unwrap())
+        .collect();
+    assert_eq!(read, witness);
+
+    let mut writer = WitnessJsonWriter::<Fr, _>::new(vec![]).unwrap();
+    for f in &witness {
+        writer.write(f).unwrap();
+    }
+    assert_eq!(writer.finish().unwrap(), bytes);
+
+    let empty = WitnessJsonWriter::<Fr, _>::new(vec![]).unwrap().finish();
+    assert_eq!(empty.unwrap(), b"[\n]");
+}